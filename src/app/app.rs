@@ -1,34 +1,83 @@
 use color_eyre::Result;
 use std::{
-    path::PathBuf,
+    collections::HashMap,
+    env, fs,
+    path::{Path, PathBuf},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, TryRecvError},
+    },
+    thread,
     time::{Duration, Instant},
 };
 
 use ratatui::{
-    DefaultTerminal,
-    crossterm::{
-        self,
-        event::{self, Event, KeyCode, KeyEvent},
-        terminal,
-    },
+    crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
     widgets::ScrollbarState,
 };
 
 use crate::{
-    commands::tmux::{TmuxSession, enter_in_tmux, list_tmux_sessions, reenter_tmux_session},
-    paths::directories::{get_current_directory_contents, get_current_directory_name},
+    cli::Args,
+    commands::fzf::{
+        DEFAULT_PREVIEW, DEFAULT_SESSION_PREVIEW, FuzzyMatch, fuzzy_match, pick_with_fzf,
+        rank_matches,
+    },
+    commands::git::{
+        GitStatus, GitWorktree, add_worktree, git_statuses, is_git_repository, list_worktrees,
+        repository_name,
+    },
+    commands::tmux::{
+        TmuxError, TmuxSession, TmuxVersion, clone_tmux_session, enter_in_tmux, list_tmux_sessions,
+        reenter_tmux_session, sanitize_session_name, session_name_for_path, start_tmux_server,
+        tmux_version,
+    },
+    config::{
+        frecency::{EntryKind, FrecencyEntry, FrecencyStore},
+        settings::{Config, project_environment},
+        state::State,
+    },
+    paths::{
+        directories::{
+            ListingFilter, complete_directory, expand_tilde, get_current_directory_contents,
+            get_current_directory_name, sort_entries,
+        },
+        metadata::{EntryMetadata, read_metadata},
+        preview::{Preview, load_preview},
+        projects::{discover_projects, display_path},
+        walk::spawn_directory_walk,
+    },
+    terminal::{self as tui, Tui},
     ui::draw,
 };
 
+/// Subtree search only shows the best matches.
+const MAX_SUBTREE_RESULTS: usize = 500;
+/// Subtree search stops walking once it has found this many directories.
+const MAX_SUBTREE_PATHS: usize = 100_000;
+/// Oldest back history entries are dropped past this.
+const MAX_HISTORY: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MarkAction {
+    /// `m`, waiting for the letter to mark the current directory with.
+    Set,
+    /// `'`, waiting for the letter of the mark to jump to.
+    Jump,
+}
+
 #[derive(Default)]
 pub struct App {
     pub show_help: bool,
-    pub selected_tab: usize, // 0 or 1
+    pub selected_tab: usize, // 0, 1, 2 or 3
 
     pub show_hidden: bool,
+    pub respect_ignore: bool,
 
     pub search_matches: Vec<usize>,
     pub search_match_index: Option<usize>,
+    /// Matched char positions per list index, for highlighting.
+    pub search_highlights: HashMap<usize, Vec<usize>>,
 
     pub searched_string: String,
     pub searching: bool,
@@ -57,37 +106,209 @@ pub struct App {
     pub tmux_visible_height: usize,
     pub tmux_scroll_state: ScrollbarState,
 
+    // Tab 2 scroller
+    pub projects_selected_line: usize,
+    pub projects_scroll: usize,
+    pub projects_total_lines: usize,
+    pub projects_visible_height: usize,
+    pub projects_scroll_state: ScrollbarState,
+
+    // Tab 3 scroller
+    pub recent_selected_line: usize,
+    pub recent_scroll: usize,
+    pub recent_total_lines: usize,
+    pub recent_visible_height: usize,
+    pub recent_scroll_state: ScrollbarState,
+
+    /// `pinta [PATH]`, used instead of the working directory on startup.
+    start_directory: Option<PathBuf>,
+    /// `--search`, opens the search prompt on startup.
+    start_searching: bool,
+    /// `--pick`, Enter and t select an entry to print instead of opening it.
+    pick: bool,
+    picked: Option<String>,
+    /// Set by the `pinta init` shell function, which cds into the
+    /// directory written here after Pinta exits.
+    cwd_file: Option<PathBuf>,
     pub current_directory: PathBuf,
+    /// Entries shown in the directories tab, narrowed by `directory_filter`.
     pub current_directory_contents: Vec<PathBuf>,
+    unfiltered_directory_contents: Vec<PathBuf>,
+    /// Only filled while the metadata columns are shown.
+    pub directory_metadata: HashMap<PathBuf, EntryMetadata>,
+    /// Branch and dirty state of the repositories in the listing, filled in
+    /// by a background thread.
+    pub git_statuses: HashMap<PathBuf, GitStatus>,
+    git_status_receiver: Option<Receiver<(PathBuf, GitStatus)>>,
+    /// Preview of the last previewed entry, reloaded when the selection moves.
+    preview: Option<(PathBuf, Preview)>,
+
+    /// Directories left by `change_directory`, most recent last.
+    back_history: Vec<PathBuf>,
+    /// Directories left by going back, for going forward again.
+    forward_history: Vec<PathBuf>,
+    /// Entry that was highlighted when each directory was last left.
+    last_selected: HashMap<PathBuf, PathBuf>,
+    /// Why the current directory could not be read, shown in the pane.
+    pub directory_error: Option<String>,
+
+    pub directory_filter: String,
+    /// Typing into the filter prompt.
+    pub filtering: bool,
+    /// Matched char positions per filtered index.
+    pub filter_highlights: HashMap<usize, Vec<usize>>,
+
+    // recursive subtree search
+    pub subtree_searching: bool,
+    pub subtree_query: String,
+    /// Directories found below `current_directory` so far, relative to it.
+    pub subtree_paths: Vec<PathBuf>,
+    /// Best matches first, as indices into `subtree_paths`.
+    pub subtree_results: Vec<(usize, FuzzyMatch)>,
+    /// Every path matching the query, in walk order. Typing only narrows
+    /// this, so a longer query is matched against these paths alone.
+    subtree_matching: Vec<usize>,
+    pub subtree_walking: bool,
+    /// The walk stopped at `MAX_SUBTREE_PATHS`.
+    pub subtree_truncated: bool,
+    pub subtree_selected_line: usize,
+    pub subtree_scroll: usize,
+    pub subtree_visible_height: usize,
+    subtree_receiver: Option<Receiver<Vec<PathBuf>>>,
+    subtree_cancel: Arc<AtomicBool>,
     pub tmux_sessions: Vec<TmuxSession>,
+    pub tmux_error: Option<TmuxError>,
+    pub tmux_version: Option<TmuxVersion>,
+
+    // go-to-path prompt
+    /// Typing a path to jump to, opened with `g` or `:`.
+    pub going_to: bool,
+    pub goto_path: String,
+    /// Candidates from the last Tab press when it was ambiguous.
+    pub goto_completions: Vec<String>,
+    pub goto_error: Option<String>,
+
+    // marks and bookmarks overlay
+    pending_mark: Option<MarkAction>,
+    /// Overlay listing the marks, opened with `b`.
+    pub showing_bookmarks: bool,
+    pub bookmark_selected_line: usize,
+    /// Typing a new path for the selected mark.
+    pub editing_bookmark: bool,
+    pub bookmark_path: String,
+    pub bookmark_error: Option<String>,
+
+    // git worktrees popup
+    /// Worktrees of the repository picked with `w`.
+    pub showing_worktrees: bool,
+    pub worktrees: Vec<GitWorktree>,
+    pub worktree_selected_line: usize,
+    /// Typing the branch for a new worktree.
+    pub creating_worktree: bool,
+    pub worktree_branch: String,
+    pub worktree_error: Option<String>,
+
+    // clone session prompt
+    pub cloning_session: bool,
+    pub clone_session_name: String,
+    pub clone_session_error: Option<String>,
+
+    pub projects: Vec<PathBuf>,
+    pub projects_scanning: bool,
+    projects_receiver: Option<Receiver<Vec<PathBuf>>>,
+
+    pub frecency: FrecencyStore,
+    /// Snapshot of the frecency store, highest score first.
+    pub recent: Vec<FrecencyEntry>,
+
+    /// One-line message under the panes, cleared on the next key press.
+    pub status_message: Option<String>,
+    /// Set after handing the terminal to another program, so the next draw
+    /// repaints everything.
+    needs_clear: bool,
+
+    pub config: Config,
+    pub state: State,
     exit: bool,
 }
 
 impl App {
-    pub fn run(mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+    pub fn new(config: Config, args: &Args) -> Self {
+        let (state, state_warning) = State::load();
+        Self {
+            show_hidden: args.hidden,
+            selected_tab: if args.tmux { 1 } else { 0 },
+            start_directory: args.path.clone(),
+            start_searching: args.search,
+            pick: args.pick,
+            cwd_file: args.cwd_file.clone(),
+            respect_ignore: config.respect_ignore,
+            config,
+            state,
+            status_message: state_warning,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the selection when started with `--pick`.
+    pub fn run(mut self, terminal: &mut Tui) -> Result<Option<String>> {
         let tick_rate = Duration::from_millis(250);
         let mut last_tick = Instant::now();
-        self.tmux_sessions = list_tmux_sessions();
+        self.refresh_tmux_sessions();
 
         self.initalize_state();
+        if self.start_searching {
+            self.start_search();
+        }
+        self.start_project_scan();
+        self.frecency = FrecencyStore::load().unwrap_or_default();
 
         loop {
+            self.poll_project_scan();
+            self.poll_subtree_walk();
+            self.poll_git_status();
+            if self.needs_clear {
+                terminal.clear()?;
+                self.needs_clear = false;
+            }
             terminal.draw(|frame| draw(&mut self, frame))?;
 
             let timeout = tick_rate.saturating_sub(last_tick.elapsed());
 
-            if event::poll(timeout)? {
-                if let Event::Key(key) = event::read()? {
-                    if self.show_help {
-                        self.key_handler_help(key);
-                    } else if self.searching {
-                        self.key_handler_searching(key);
-                    } else {
-                        match self.selected_tab {
-                            0 => self.key_handler_directories(key),
-                            1 => self.key_handler_tmux(key),
-                            _ => {}
-                        }
+            if event::poll(timeout)?
+                && let Event::Key(key) = event::read()?
+            {
+                self.status_message = None;
+
+                if self.show_help {
+                    self.key_handler_help(key);
+                } else if self.searching {
+                    self.key_handler_searching(key);
+                } else if self.subtree_searching {
+                    self.key_handler_subtree_search(key);
+                } else if self.filtering {
+                    self.key_handler_filtering(key);
+                } else if self.cloning_session {
+                    self.key_handler_clone_session(key);
+                } else if self.showing_worktrees {
+                    self.key_handler_worktrees(key);
+                } else if self.going_to {
+                    self.key_handler_goto(key);
+                } else if self.showing_bookmarks {
+                    self.key_handler_bookmarks(key);
+                } else if let Some(action) = self.pending_mark.take() {
+                    self.key_handler_mark(action, key);
+                } else if self.pick && matches!(key.code, KeyCode::Enter | KeyCode::Char('t')) {
+                    if let Some(target) = self.pick_target() {
+                        self.pick_instead(target);
+                    }
+                } else {
+                    match self.selected_tab {
+                        0 => self.key_handler_directories(key),
+                        1 => self.key_handler_tmux(key),
+                        2 => self.key_handler_projects(key),
+                        3 => self.key_handler_recent(key),
+                        _ => {}
                     }
                 }
             }
@@ -97,7 +318,7 @@ impl App {
             }
 
             if self.exit {
-                return Ok(());
+                return Ok(self.picked);
             }
         }
     }
@@ -111,131 +332,131 @@ impl App {
                 self.show_help = !self.show_help;
             }
 
-            KeyCode::Char('2') => self.selected_tab = 1,
+            KeyCode::Char('2') => self.select_tab(1),
+            KeyCode::Char('3') => self.select_tab(2),
+            KeyCode::Char('4') => self.select_tab(3),
 
-            KeyCode::Char('/') => {
-                self.searching = true;
-                self.searched_string.clear();
-                self.search_matches.clear();
-                self.search_match_index = None;
-                self.last_search_update = Some(Instant::now());
-                self.update_search_results();
+            KeyCode::Char('f') => self.pick_externally(),
+
+            KeyCode::Char('/') => self.start_search(),
+
+            KeyCode::Char('j' | 'k') | KeyCode::Down | KeyCode::Up
+                if self.directories_total_lines > 0 =>
+            {
+                move_selection(
+                    &mut self.directories_selected_line,
+                    &mut self.directories_scroll,
+                    self.directories_total_lines,
+                    self.directories_visible_height,
+                    key.code,
+                );
             }
 
-            KeyCode::Char('j') | KeyCode::Down => {
-                if self.directories_total_lines > 0 {
-                    let prev_line = self.directories_selected_line;
-                    self.directories_selected_line =
-                        (self.directories_selected_line + 1) % self.directories_total_lines;
-
-                    if self.directories_selected_line == 0
-                        && prev_line == self.directories_total_lines - 1
-                    {
-                        self.directories_scroll = 0;
-                    } else if self.directories_selected_line
-                        >= self.directories_scroll + self.directories_visible_height
-                    {
-                        self.directories_scroll = self.directories_scroll.saturating_add(1);
-                    }
-                }
+            KeyCode::Char('n') if !self.search_matches.is_empty() => {
+                self.jump_to_next_match();
             }
 
-            KeyCode::Char('k') | KeyCode::Up => {
-                if self.directories_total_lines > 0 {
-                    let prev_line = self.directories_selected_line;
-                    if self.directories_selected_line == 0 {
-                        self.directories_selected_line = self.directories_total_lines - 1;
-                        self.directories_scroll = self
-                            .directories_total_lines
-                            .saturating_sub(self.directories_visible_height)
-                            .min(self.directories_total_lines.saturating_sub(1));
-                    } else {
-                        self.directories_selected_line -= 1;
-                        if self.directories_selected_line < self.directories_scroll {
-                            self.directories_scroll = self.directories_scroll.saturating_sub(1);
-                        }
-                    }
+            KeyCode::Char('A') => {
+                self.show_hidden = !self.show_hidden;
+                self.reload_directory();
+            }
 
-                    if prev_line == 0
-                        && self.directories_selected_line == self.directories_total_lines - 1
-                    {
-                        self.directories_scroll = self
-                            .directories_total_lines
-                            .saturating_sub(self.directories_visible_height)
-                            .min(self.directories_total_lines.saturating_sub(1));
-                    }
-                }
+            KeyCode::Char('I') => {
+                self.respect_ignore = !self.respect_ignore;
+                self.reload_directory();
             }
 
-            KeyCode::Char('n') => {
-                if !self.search_matches.is_empty() {
-                    self.jump_to_next_match();
+            KeyCode::Char('M') => {
+                self.state.show_metadata = !self.state.show_metadata;
+                self.reload_keeping_selection();
+                if let Err(e) = self.state.save() {
+                    self.status_message = Some(format!("Could not save settings: {}", e));
                 }
             }
 
-            KeyCode::Char('A') => {
-                self.show_hidden = !self.show_hidden;
-                self.search_matches = Vec::new();
-                self.search_match_index = None;
-                self.searched_string = String::new();
-                self.current_directory_contents = get_current_directory_contents(
-                    self.current_directory.as_path(),
-                    self.show_hidden,
-                );
-                self.directories_selected_line = 0;
-                self.directories_scroll = 0;
+            KeyCode::Char('p') => self.toggle_preview(),
+
+            KeyCode::Char('o') => {
+                self.state.sort.mode = self.state.sort.mode.next();
+                self.sort_changed();
             }
 
-            KeyCode::Char('N') => {
-                if !self.search_matches.is_empty() {
-                    self.jump_to_prev_match();
-                }
+            KeyCode::Char('O') => {
+                self.state.sort.reverse = !self.state.sort.reverse;
+                self.sort_changed();
+            }
+
+            KeyCode::Char('D') => {
+                self.state.sort.directories_first = !self.state.sort.directories_first;
+                self.sort_changed();
+            }
+
+            KeyCode::Char('N') if !self.search_matches.is_empty() => {
+                self.jump_to_prev_match();
             }
 
             KeyCode::Char('h') | KeyCode::Left => {
                 if let Some(parent) = self.current_directory.parent() {
-                    self.search_matches = Vec::new();
-                    self.search_match_index = None;
-                    self.searched_string = String::new();
-                    self.current_directory = parent.to_path_buf();
-                    self.current_directory_contents = get_current_directory_contents(
-                        self.current_directory.as_path(),
-                        self.show_hidden,
-                    );
-                    self.directories_selected_line = 0;
-                    self.directories_scroll = 0;
+                    self.change_directory(parent.to_path_buf());
                 }
             }
 
             KeyCode::Char('l') | KeyCode::Right => {
-                let selected_path =
-                    &self.current_directory_contents[self.directories_selected_line];
-                if selected_path.is_dir() {
-                    self.search_matches = Vec::new();
-                    self.search_match_index = None;
-                    self.searched_string = String::new();
-
-                    self.current_directory
-                        .push(selected_path.file_name().unwrap());
-                    self.current_directory_contents =
-                        get_current_directory_contents(&self.current_directory, self.show_hidden);
-                    self.directories_selected_line = 0;
-                    self.directories_scroll = 0;
+                if let Some(selected_path) = self.selected_directory() {
+                    self.change_directory(selected_path);
                 }
             }
 
-            KeyCode::Char('t') => {
-                terminal::disable_raw_mode().expect("Could not disable raw mode");
-                crossterm::execute!(std::io::stdout(), crossterm::terminal::LeaveAlternateScreen)
-                    .expect("Could not leave alternate screen");
+            KeyCode::Char('F') => {
+                self.filtering = true;
+            }
+
+            KeyCode::Char('s') => self.start_subtree_search(),
+
+            KeyCode::Char('w') => self.show_worktrees(),
+
+            KeyCode::Char('c') => self.exit_to_directory(),
+
+            KeyCode::Char('g') => self.start_goto(""),
+            KeyCode::Char(':') => self.start_goto("cd "),
+
+            KeyCode::Char('m') => self.pending_mark = Some(MarkAction::Set),
+            KeyCode::Char('\'') => self.pending_mark = Some(MarkAction::Jump),
+            KeyCode::Char('b') => {
+                self.bookmark_selected_line = 0;
+                self.bookmark_error = None;
+                self.editing_bookmark = false;
+                self.showing_bookmarks = true;
+            }
+
+            KeyCode::Char('[') => self.go_back(),
+            KeyCode::Char(']') => self.go_forward(),
 
-                let selected_path = &self.current_directory;
+            KeyCode::Esc if !self.directory_filter.is_empty() => {
+                self.directory_filter.clear();
+                self.apply_directory_filter();
+            }
 
-                if selected_path.is_dir() {
-                    enter_in_tmux(selected_path);
+            // With a filter, Enter and t act on the highlighted entry.
+            KeyCode::Enter if !self.directory_filter.is_empty() => {
+                if let Some(selected_path) = self.selected_directory() {
+                    self.change_directory(selected_path);
                 }
+            }
 
-                self.exit = true;
+            // Show the Tmux pane with the reason instead of exiting into a broken tmux.
+            KeyCode::Char('t') if !self.tmux_usable() => self.select_tab(1),
+
+            KeyCode::Char('t') => {
+                let selected_path = if self.directory_filter.is_empty() {
+                    Some(self.current_directory.clone())
+                } else {
+                    self.selected_directory()
+                };
+
+                if let Some(selected_path) = selected_path {
+                    self.open_in_tmux(&selected_path);
+                }
             }
 
             _ => {}
@@ -251,77 +472,100 @@ impl App {
                 self.show_help = !self.show_help;
             }
 
-            KeyCode::Char('1') => self.selected_tab = 0,
+            KeyCode::Char('1') => self.select_tab(0),
+            KeyCode::Char('3') => self.select_tab(2),
+            KeyCode::Char('4') => self.select_tab(3),
 
-            KeyCode::Char('j') | KeyCode::Down => {
-                if !self.tmux_sessions.is_empty() {
-                    self.tmux_selected_line =
-                        (self.tmux_selected_line + 1) % self.tmux_sessions.len();
+            KeyCode::Char('j') | KeyCode::Down if !self.tmux_sessions.is_empty() => {
+                self.tmux_selected_line = (self.tmux_selected_line + 1) % self.tmux_sessions.len();
 
-                    let mut line_index = 0;
-                    for (i, session) in self.tmux_sessions.iter().enumerate() {
-                        if i == self.tmux_selected_line {
-                            break;
-                        }
-                        line_index += 1 + session.windows.len() + 1;
+                let mut line_index = 0;
+                for (i, session) in self.tmux_sessions.iter().enumerate() {
+                    if i == self.tmux_selected_line {
+                        break;
                     }
+                    line_index += 1 + session.windows.len() + 1;
+                }
 
-                    let session_height =
-                        1 + self.tmux_sessions[self.tmux_selected_line].windows.len() + 1;
+                let session_height =
+                    1 + self.tmux_sessions[self.tmux_selected_line].windows.len() + 1;
 
-                    if line_index < self.tmux_scroll {
-                        self.tmux_scroll = line_index;
-                    } else if line_index + session_height
-                        > self.tmux_scroll + self.tmux_visible_height
-                    {
-                        self.tmux_scroll = line_index + session_height - self.tmux_visible_height;
-                    }
+                if line_index < self.tmux_scroll {
+                    self.tmux_scroll = line_index;
+                } else if line_index + session_height > self.tmux_scroll + self.tmux_visible_height
+                {
+                    self.tmux_scroll = line_index + session_height - self.tmux_visible_height;
                 }
             }
 
-            KeyCode::Char('k') | KeyCode::Up => {
-                if !self.tmux_sessions.is_empty() {
-                    if self.tmux_selected_line == 0 {
-                        self.tmux_selected_line = self.tmux_sessions.len() - 1;
-                    } else {
-                        self.tmux_selected_line -= 1;
-                    }
+            KeyCode::Char('k') | KeyCode::Up if !self.tmux_sessions.is_empty() => {
+                if self.tmux_selected_line == 0 {
+                    self.tmux_selected_line = self.tmux_sessions.len() - 1;
+                } else {
+                    self.tmux_selected_line -= 1;
+                }
 
-                    let mut line_index = 0;
-                    for (i, session) in self.tmux_sessions.iter().enumerate() {
-                        if i == self.tmux_selected_line {
-                            break;
-                        }
-                        line_index += 1 + session.windows.len() + 1;
+                let mut line_index = 0;
+                for (i, session) in self.tmux_sessions.iter().enumerate() {
+                    if i == self.tmux_selected_line {
+                        break;
                     }
+                    line_index += 1 + session.windows.len() + 1;
+                }
 
-                    let session_height =
-                        1 + self.tmux_sessions[self.tmux_selected_line].windows.len() + 1;
+                let session_height =
+                    1 + self.tmux_sessions[self.tmux_selected_line].windows.len() + 1;
 
-                    if line_index < self.tmux_scroll {
-                        self.tmux_scroll = line_index;
-                    } else if line_index + session_height
-                        > self.tmux_scroll + self.tmux_visible_height
-                    {
-                        self.tmux_scroll = line_index + session_height - self.tmux_visible_height;
-                    }
+                if line_index < self.tmux_scroll {
+                    self.tmux_scroll = line_index;
+                } else if line_index + session_height > self.tmux_scroll + self.tmux_visible_height
+                {
+                    self.tmux_scroll = line_index + session_height - self.tmux_visible_height;
                 }
             }
 
-            KeyCode::Char('t') => {
-                if !self.tmux_sessions.is_empty() {
-                    terminal::disable_raw_mode().expect("Could not disable raw mode");
-                    crossterm::execute!(
-                        std::io::stdout(),
-                        crossterm::terminal::LeaveAlternateScreen
-                    )
-                    .expect("Could not leave alternate screen");
-
-                    if let Some(session) = self.tmux_sessions.get(self.tmux_selected_line) {
-                        reenter_tmux_session(&session.name);
-                    }
+            KeyCode::Char('t') if !self.tmux_sessions.is_empty() => {
+                if let Some(session) = self.tmux_sessions.get(self.tmux_selected_line) {
+                    let name = session.name.clone();
+                    self.attach_session(&name);
+                }
+            }
 
-                    self.exit = true;
+            KeyCode::Char('r') => self.refresh_tmux_sessions(),
+
+            KeyCode::Char('f') if !self.tmux_sessions.is_empty() => self.pick_externally(),
+
+            KeyCode::Char('/') => self.start_search(),
+
+            KeyCode::Char('n') if !self.search_matches.is_empty() => {
+                self.jump_to_next_match();
+            }
+
+            KeyCode::Char('N') if !self.search_matches.is_empty() => {
+                self.jump_to_prev_match();
+            }
+
+            KeyCode::Char('s') if self.pick => {
+                self.status_message =
+                    Some("Starting a tmux server is not available with --pick".into());
+            }
+
+            KeyCode::Char('s') if self.tmux_error == Some(TmuxError::NoServer) => {
+                match start_tmux_server() {
+                    Ok(()) => self.refresh_tmux_sessions(),
+                    Err(e) => self.tmux_error = Some(e),
+                }
+            }
+
+            KeyCode::Char('c') if self.pick => {
+                self.status_message = Some("Cloning sessions is not available with --pick".into());
+            }
+
+            KeyCode::Char('c') => {
+                if let Some(session) = self.tmux_sessions.get(self.tmux_selected_line) {
+                    self.clone_session_name = self.free_session_name(&session.name);
+                    self.clone_session_error = None;
+                    self.cloning_session = true;
                 }
             }
 
@@ -329,179 +573,1555 @@ impl App {
         }
     }
 
-    fn key_handler_help(&mut self, key: KeyEvent) {
+    fn key_handler_projects(&mut self, key: KeyEvent) {
         match key.code {
-            KeyCode::Char('q') | KeyCode::Char('H') => {
+            KeyCode::Char('q') => {
+                self.exit = true;
+            }
+            KeyCode::Char('H') => {
                 self.show_help = !self.show_help;
-                return;
             }
 
-            KeyCode::Char('j') | KeyCode::Down => {
-                if self.help_total_lines > 0 {
-                    let prev_line = self.help_selected_line;
-                    self.help_selected_line = (self.help_selected_line + 1) % self.help_total_lines;
+            KeyCode::Char('1') => self.select_tab(0),
+            KeyCode::Char('2') => self.select_tab(1),
+            KeyCode::Char('4') => self.select_tab(3),
 
-                    if self.help_selected_line == 0 && prev_line == self.help_total_lines - 1 {
-                        self.help_scroll = 0;
-                    } else if self.help_selected_line >= self.help_scroll + self.help_visible_height
-                    {
-                        self.help_scroll = self.help_scroll.saturating_add(1);
-                    }
+            KeyCode::Char('f') => self.pick_externally(),
+
+            KeyCode::Char('/') => self.start_search(),
+
+            KeyCode::Char('n') if !self.search_matches.is_empty() => {
+                self.jump_to_next_match();
+            }
+
+            KeyCode::Char('N') if !self.search_matches.is_empty() => {
+                self.jump_to_prev_match();
+            }
+
+            KeyCode::Char('j' | 'k') | KeyCode::Down | KeyCode::Up
+                if self.projects_total_lines > 0 =>
+            {
+                move_selection(
+                    &mut self.projects_selected_line,
+                    &mut self.projects_scroll,
+                    self.projects_total_lines,
+                    self.projects_visible_height,
+                    key.code,
+                );
+            }
+
+            // Browse the project in the directories tab.
+            KeyCode::Char('l') | KeyCode::Right | KeyCode::Enter => {
+                if let Some(project) = self.projects.get(self.projects_selected_line) {
+                    self.change_directory(project.clone());
+                    self.select_tab(0);
                 }
             }
 
-            KeyCode::Char('k') | KeyCode::Up => {
-                if self.help_total_lines > 0 {
-                    let prev_line = self.help_selected_line;
-                    if self.help_selected_line == 0 {
-                        self.help_selected_line = self.help_total_lines - 1;
-                        self.help_scroll = self
-                            .help_total_lines
-                            .saturating_sub(self.help_visible_height)
-                            .min(self.help_total_lines.saturating_sub(1));
-                    } else {
-                        self.help_selected_line -= 1;
-                        if self.help_selected_line < self.help_scroll {
-                            self.help_scroll = self.help_scroll.saturating_sub(1);
-                        }
-                    }
+            KeyCode::Char('r') => self.start_project_scan(),
 
-                    if prev_line == 0 && self.help_selected_line == self.help_total_lines - 1 {
-                        self.help_scroll = self
-                            .help_total_lines
-                            .saturating_sub(self.help_visible_height)
-                            .min(self.help_total_lines.saturating_sub(1));
-                    }
+            KeyCode::Char('p') => self.toggle_preview(),
+
+            KeyCode::Char('t') if !self.tmux_usable() => self.select_tab(1),
+
+            KeyCode::Char('t') => {
+                if let Some(project) = self.projects.get(self.projects_selected_line).cloned() {
+                    self.open_in_tmux(&project);
                 }
             }
 
-            KeyCode::Char('1') => self.help_selected_tab = 0,
-            KeyCode::Char('2') => self.help_selected_tab = 1,
-            KeyCode::Char('3') => self.help_selected_tab = 2,
-            KeyCode::Char('4') => self.help_selected_tab = 3,
+            _ => {}
+        }
+    }
 
-            KeyCode::Char('h') => {
-                if self.help_selected_tab == 0 {
-                    self.help_selected_tab = 3;
-                } else {
-                    self.help_selected_tab -= 1;
+    fn key_handler_recent(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char('q') => {
+                self.exit = true;
+            }
+            KeyCode::Char('H') => {
+                self.show_help = !self.show_help;
+            }
+
+            KeyCode::Char('1') => self.select_tab(0),
+            KeyCode::Char('2') => self.select_tab(1),
+            KeyCode::Char('3') => self.select_tab(2),
+
+            KeyCode::Char('f') => self.pick_externally(),
+
+            KeyCode::Char('/') => self.start_search(),
+
+            KeyCode::Char('n') if !self.search_matches.is_empty() => {
+                self.jump_to_next_match();
+            }
+
+            KeyCode::Char('N') if !self.search_matches.is_empty() => {
+                self.jump_to_prev_match();
+            }
+
+            KeyCode::Char('j' | 'k') | KeyCode::Down | KeyCode::Up
+                if self.recent_total_lines > 0 =>
+            {
+                move_selection(
+                    &mut self.recent_selected_line,
+                    &mut self.recent_scroll,
+                    self.recent_total_lines,
+                    self.recent_visible_height,
+                    key.code,
+                );
+            }
+
+            KeyCode::Char('l') | KeyCode::Right | KeyCode::Enter => {
+                if let Some(entry) = self.recent.get(self.recent_selected_line)
+                    && entry.kind == EntryKind::Directory
+                {
+                    let path = PathBuf::from(&entry.value);
+                    if path.is_dir() {
+                        self.change_directory(path);
+                        self.select_tab(0);
+                    }
                 }
             }
-            KeyCode::Char('l') => {
-                if self.help_selected_tab == 3 {
-                    self.help_selected_tab = 0;
-                } else {
-                    self.help_selected_tab += 1;
+
+            KeyCode::Char('t') if !self.tmux_usable() => self.select_tab(1),
+
+            KeyCode::Char('t') => {
+                if let Some(entry) = self.recent.get(self.recent_selected_line).cloned() {
+                    match entry.kind {
+                        EntryKind::Directory => self.open_in_tmux(Path::new(&entry.value)),
+                        EntryKind::Session
+                            if self.tmux_sessions.iter().any(|s| s.name == entry.value) =>
+                        {
+                            self.attach_session(&entry.value)
+                        }
+                        EntryKind::Session => {
+                            self.status_message =
+                                Some(format!("Session {} is not running", entry.value));
+                        }
+                    }
                 }
             }
 
+            KeyCode::Char('i') => {
+                self.status_message = Some(match self.frecency.import_zoxide() {
+                    Ok(count) => match self.frecency.save() {
+                        Ok(()) => format!("Imported {} directories from zoxide", count),
+                        Err(e) => format!("Could not save frecency store: {}", e),
+                    },
+                    Err(e) => format!("zoxide import failed: {}", e),
+                });
+                self.refresh_recent();
+            }
+
+            KeyCode::Char('p') => self.toggle_preview(),
+
             _ => {}
         }
     }
 
-    fn key_handler_searching(&mut self, key: KeyEvent) {
+    fn key_handler_filtering(&mut self, key: KeyEvent) {
         match key.code {
             KeyCode::Esc => {
-                self.searching = false;
-                self.searched_string.clear();
-                self.search_matches.clear();
-                self.search_match_index = None;
+                self.filtering = false;
+                self.directory_filter.clear();
+                self.apply_directory_filter();
             }
 
-            KeyCode::Char(c) => {
-                if !c.is_control() && !c.is_whitespace() {
-                    self.searched_string.push(c);
-
-                    self.last_search_update = Some(Instant::now());
-                    self.update_search_results();
-                }
+            KeyCode::Char(c) if !c.is_control() => {
+                self.directory_filter.push(c);
+                self.apply_directory_filter();
             }
 
             KeyCode::Backspace => {
-                self.searched_string.pop();
-                self.last_search_update = Some(Instant::now());
-                self.update_search_results();
+                self.directory_filter.pop();
+                self.apply_directory_filter();
             }
 
+            // Keep the filter and go back to navigating the narrowed list.
             KeyCode::Enter => {
-                self.last_search_update = Some(Instant::now());
-                self.searching = false;
+                self.filtering = false;
+            }
+
+            KeyCode::Down | KeyCode::Up => {
+                self.key_handler_directories(key);
             }
 
             _ => {}
         }
     }
 
-    fn update_search_results(&mut self) {
-        self.search_matches.clear();
-        self.search_match_index = None;
-
-        if self.searched_string.is_empty() {
-            return;
-        }
+    fn key_handler_subtree_search(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => self.stop_subtree_search(),
 
-        let query = self.searched_string.to_lowercase();
+            KeyCode::Enter => {
+                if let Some(path) = self.selected_subtree_path() {
+                    self.stop_subtree_search();
+                    self.change_directory(path);
+                }
+            }
 
-        for (i, path) in self.current_directory_contents.iter().enumerate() {
-            if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
-                if name.to_lowercase().contains(&query) {
-                    self.search_matches.push(i);
+            KeyCode::Char('t') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                if let Some(path) = self.selected_subtree_path() {
+                    self.stop_subtree_search();
+                    if self.tmux_usable() {
+                        self.open_in_tmux(&path);
+                    } else {
+                        self.select_tab(1);
+                    }
                 }
             }
-        }
 
-        if !self.search_matches.is_empty() {
-            self.search_match_index = Some(0);
-            self.directories_selected_line = self.search_matches[0];
-            self.ensure_selected_visible();
+            KeyCode::Down | KeyCode::Up if !self.subtree_results.is_empty() => {
+                move_selection(
+                    &mut self.subtree_selected_line,
+                    &mut self.subtree_scroll,
+                    self.subtree_results.len(),
+                    self.subtree_visible_height,
+                    key.code,
+                );
+            }
+
+            KeyCode::Char(c) if !c.is_control() => {
+                self.subtree_query.push(c);
+                self.narrow_subtree_results();
+            }
+
+            KeyCode::Backspace => {
+                self.subtree_query.pop();
+                self.rank_subtree_paths(0);
+            }
+
+            _ => {}
         }
     }
 
-    fn initalize_state(&mut self) {
-        self.current_directory = get_current_directory_name();
-        self.current_directory_contents =
-            get_current_directory_contents(self.current_directory.as_path(), self.show_hidden);
+    fn start_subtree_search(&mut self) {
+        self.subtree_cancel = Arc::new(AtomicBool::new(false));
+        self.subtree_receiver = Some(spawn_directory_walk(
+            self.current_directory.clone(),
+            self.listing_filter(),
+            self.subtree_cancel.clone(),
+        ));
+        self.subtree_searching = true;
+        self.subtree_walking = true;
+        self.subtree_truncated = false;
+        self.subtree_query.clear();
+        self.subtree_paths.clear();
+        self.subtree_results.clear();
+        self.subtree_matching.clear();
+        self.subtree_selected_line = 0;
+        self.subtree_scroll = 0;
     }
 
-    fn jump_to_next_match(&mut self) {
-        if self.search_matches.is_empty() {
-            return;
-        }
+    fn stop_subtree_search(&mut self) {
+        self.subtree_cancel.store(true, Ordering::Relaxed);
+        self.subtree_receiver = None;
+        self.subtree_searching = false;
+        self.subtree_walking = false;
+        self.subtree_paths.clear();
+        self.subtree_results.clear();
+        self.subtree_matching.clear();
+    }
 
-        let next_index = match self.search_match_index {
-            Some(i) => (i + 1) % self.search_matches.len(),
-            None => 0,
+    fn poll_subtree_walk(&mut self) {
+        let Some(receiver) = &self.subtree_receiver else {
+            return;
         };
 
-        self.search_match_index = Some(next_index);
-        self.directories_selected_line = self.search_matches[next_index];
-        self.ensure_selected_visible();
+        let start = self.subtree_paths.len();
+        loop {
+            match receiver.try_recv() {
+                Ok(mut batch) => {
+                    let room = MAX_SUBTREE_PATHS - self.subtree_paths.len();
+                    if batch.len() >= room {
+                        batch.truncate(room);
+                        self.subtree_paths.extend(batch);
+                        self.subtree_cancel.store(true, Ordering::Relaxed);
+                        self.subtree_receiver = None;
+                        self.subtree_walking = false;
+                        self.subtree_truncated = true;
+                        break;
+                    }
+                    self.subtree_paths.extend(batch);
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.subtree_receiver = None;
+                    self.subtree_walking = false;
+                    break;
+                }
+            }
+        }
+
+        if self.subtree_paths.len() > start {
+            self.rank_subtree_paths(start);
+        }
     }
 
-    fn jump_to_prev_match(&mut self) {
-        if self.search_matches.is_empty() {
-            return;
+    /// Ranks the paths from `start` on against the query and merges them into
+    /// the best results, so streamed batches are only matched once. `0`
+    /// re-ranks everything. Without a query the shortest paths come first.
+    fn rank_subtree_paths(&mut self, start: usize) {
+        if start == 0 {
+            self.subtree_results.clear();
+            self.subtree_matching.clear();
         }
+        self.rank_subtree_candidates((start..self.subtree_paths.len()).collect());
+    }
 
-        let prev_index = match self.search_match_index {
-            Some(0) | None => self.search_matches.len() - 1,
-            Some(i) => i - 1,
-        };
+    /// Re-ranks after the query grew. Paths that did not match before
+    /// cannot match now, so only the previous matches are looked at.
+    fn narrow_subtree_results(&mut self) {
+        let candidates = std::mem::take(&mut self.subtree_matching);
+        self.subtree_results.clear();
+        self.rank_subtree_candidates(candidates);
+    }
 
-        self.search_match_index = Some(prev_index);
-        self.directories_selected_line = self.search_matches[prev_index];
-        self.ensure_selected_visible();
+    /// Matches `candidates`, indices into `subtree_paths` in walk order,
+    /// and merges them into `subtree_matching` and the best results.
+    fn rank_subtree_candidates(&mut self, candidates: Vec<usize>) {
+        let selected = self
+            .subtree_results
+            .get(self.subtree_selected_line)
+            .map(|(i, _)| *i);
+
+        let names: Vec<String> = candidates
+            .iter()
+            .map(|&i| self.subtree_paths[i].to_string_lossy().into_owned())
+            .collect();
+        let ranked = rank_matches(&names, &self.subtree_query);
+
+        let mut matching: Vec<usize> = ranked.iter().map(|(j, _)| candidates[*j]).collect();
+        matching.sort_unstable();
+        self.subtree_matching.extend(matching);
+
+        self.subtree_results.extend(
+            ranked
+                .into_iter()
+                .take(MAX_SUBTREE_RESULTS)
+                .map(|(j, fuzzy)| (candidates[j], fuzzy)),
+        );
+
+        // Same order as `rank_matches`: score, then length, then walk order.
+        let paths = &self.subtree_paths;
+        self.subtree_results.sort_by_cached_key(|(i, fuzzy)| {
+            (
+                std::cmp::Reverse(fuzzy.score),
+                paths[*i].to_string_lossy().chars().count(),
+                *i,
+            )
+        });
+        self.subtree_results.truncate(MAX_SUBTREE_RESULTS);
+
+        // Keep the highlighted path while results stream in.
+        self.subtree_selected_line = selected
+            .and_then(|selected| {
+                self.subtree_results
+                    .iter()
+                    .position(|(i, _)| *i == selected)
+            })
+            .unwrap_or(0);
+        self.ensure_subtree_selected_visible();
     }
 
-    fn ensure_selected_visible(&mut self) {
-        if self.directories_selected_line < self.directories_scroll {
-            self.directories_scroll = self.directories_selected_line;
-        } else if self.directories_selected_line
-            >= self.directories_scroll + self.directories_visible_height
-        {
-            self.directories_scroll = self
-                .directories_selected_line
-                .saturating_sub(self.directories_visible_height - 1);
+    fn selected_subtree_path(&self) -> Option<PathBuf> {
+        self.subtree_results
+            .get(self.subtree_selected_line)
+            .map(|(i, _)| self.current_directory.join(&self.subtree_paths[*i]))
+    }
+
+    fn ensure_subtree_selected_visible(&mut self) {
+        scroll_to_line(
+            self.subtree_selected_line,
+            &mut self.subtree_scroll,
+            self.subtree_visible_height,
+        );
+    }
+
+    fn key_handler_clone_session(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                self.cloning_session = false;
+            }
+
+            KeyCode::Char(c) if !c.is_control() && !c.is_whitespace() => {
+                self.clone_session_name.push(c);
+            }
+
+            KeyCode::Backspace => {
+                self.clone_session_name.pop();
+            }
+
+            KeyCode::Enter => {
+                let Some(source) = self.tmux_sessions.get(self.tmux_selected_line) else {
+                    self.cloning_session = false;
+                    return;
+                };
+
+                let new_name = sanitize_session_name(&self.clone_session_name);
+                if new_name.is_empty() {
+                    self.clone_session_error = Some("Session name cannot be empty".to_string());
+                    return;
+                }
+                if self.tmux_sessions.iter().any(|s| s.name == new_name) {
+                    self.clone_session_error = Some(format!("Session {} already exists", new_name));
+                    return;
+                }
+
+                match clone_tmux_session(&source.name, &new_name) {
+                    Ok(()) => {
+                        self.cloning_session = false;
+                        self.refresh_tmux_sessions();
+                        if let Some(i) = self.tmux_sessions.iter().position(|s| s.name == new_name)
+                        {
+                            self.tmux_selected_line = i;
+                        }
+                    }
+                    Err(e) => self.clone_session_error = Some(e.to_string()),
+                }
+            }
+
+            _ => {}
         }
     }
+
+    /// Quits and leaves the shell in the current directory, through the
+    /// file the shell function reads after Pinta exits.
+    fn exit_to_directory(&mut self) {
+        let Some(cwd_file) = &self.cwd_file else {
+            self.status_message = Some(
+                "Run pinta through the function from `pinta init bash|zsh|fish` to cd on exit"
+                    .to_string(),
+            );
+            return;
+        };
+
+        match fs::write(
+            cwd_file,
+            self.current_directory.as_os_str().as_encoded_bytes(),
+        ) {
+            Ok(()) => self.exit = true,
+            Err(e) => {
+                self.status_message = Some(format!("Could not write {}: {}", cwd_file.display(), e))
+            }
+        }
+    }
+
+    fn start_goto(&mut self, text: &str) {
+        self.goto_path = text.to_string();
+        self.goto_completions.clear();
+        self.goto_error = None;
+        self.going_to = true;
+    }
+
+    /// The prompt takes a bare path or `cd <path>`, absolute, `~/...` or
+    /// relative to the current directory.
+    fn key_handler_goto(&mut self, key: KeyEvent) {
+        let prefix = if self.goto_path.starts_with("cd ") {
+            "cd "
+        } else {
+            ""
+        };
+
+        match key.code {
+            KeyCode::Esc => {
+                self.going_to = false;
+            }
+
+            KeyCode::Tab => {
+                let (completed, candidates) = complete_directory(
+                    &self.goto_path[prefix.len()..],
+                    &self.current_directory,
+                    self.show_hidden,
+                );
+                self.goto_path = format!("{}{}", prefix, completed);
+                self.goto_completions = candidates;
+                self.goto_error = None;
+            }
+
+            KeyCode::Char(c) if !c.is_control() => {
+                self.goto_path.push(c);
+                self.goto_completions.clear();
+            }
+
+            KeyCode::Backspace => {
+                self.goto_path.pop();
+                self.goto_completions.clear();
+            }
+
+            KeyCode::Enter => {
+                let input = self.goto_path[prefix.len()..].trim();
+                let path =
+                    self.current_directory
+                        .join(expand_tilde(Path::new(if input.is_empty() {
+                            "~"
+                        } else {
+                            input
+                        })));
+
+                match path.canonicalize() {
+                    Ok(path) if path.is_dir() => {
+                        self.going_to = false;
+                        self.change_directory(path);
+                    }
+                    Ok(path) => {
+                        self.goto_error = Some(format!("{} is not a directory", path.display()))
+                    }
+                    Err(e) => self.goto_error = Some(format!("{}: {}", path.display(), e)),
+                }
+            }
+
+            _ => {}
+        }
+    }
+
+    fn key_handler_mark(&mut self, action: MarkAction, key: KeyEvent) {
+        let KeyCode::Char(letter) = key.code else {
+            return;
+        };
+        if !letter.is_ascii_alphabetic() {
+            self.status_message = Some(format!("Marks are letters, not {}", letter));
+            return;
+        }
+
+        match action {
+            MarkAction::Set => {
+                self.state
+                    .marks
+                    .insert(letter.to_string(), self.current_directory.clone());
+                self.save_marks();
+            }
+            MarkAction::Jump => match self.state.marks.get(&letter.to_string()).cloned() {
+                Some(path) => self.change_directory(path),
+                None => self.status_message = Some(format!("Mark {} is not set", letter)),
+            },
+        }
+    }
+
+    fn save_marks(&mut self) {
+        if let Err(e) = self.state.save() {
+            self.status_message = Some(format!("Could not save marks: {}", e));
+        }
+    }
+
+    /// Mark letter and path at `bookmark_selected_line`.
+    fn selected_bookmark(&self) -> Option<(String, PathBuf)> {
+        self.state
+            .marks
+            .iter()
+            .nth(self.bookmark_selected_line)
+            .map(|(letter, path)| (letter.clone(), path.clone()))
+    }
+
+    fn key_handler_bookmarks(&mut self, key: KeyEvent) {
+        if self.editing_bookmark {
+            match key.code {
+                KeyCode::Esc => {
+                    self.editing_bookmark = false;
+                    self.bookmark_error = None;
+                }
+
+                KeyCode::Char(c) if !c.is_control() => {
+                    self.bookmark_path.push(c);
+                }
+
+                KeyCode::Backspace => {
+                    self.bookmark_path.pop();
+                }
+
+                KeyCode::Enter => {
+                    // Resolved like the go-to prompt, so a relative path
+                    // keeps pointing at the same directory later on.
+                    let path = self
+                        .current_directory
+                        .join(expand_tilde(Path::new(self.bookmark_path.trim())));
+                    let path = match path.canonicalize() {
+                        Ok(path) if path.is_dir() => path,
+                        Ok(path) => {
+                            self.bookmark_error =
+                                Some(format!("{} is not a directory", path.display()));
+                            return;
+                        }
+                        Err(e) => {
+                            self.bookmark_error = Some(format!("{}: {}", path.display(), e));
+                            return;
+                        }
+                    };
+                    if let Some((letter, _)) = self.selected_bookmark() {
+                        self.state.marks.insert(letter, path);
+                        self.save_marks();
+                    }
+                    self.editing_bookmark = false;
+                    self.bookmark_error = None;
+                }
+
+                _ => {}
+            }
+            return;
+        }
+
+        let count = self.state.marks.len();
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('b') => {
+                self.showing_bookmarks = false;
+            }
+
+            KeyCode::Char('j' | 'k') | KeyCode::Down | KeyCode::Up if count > 0 => {
+                self.bookmark_selected_line =
+                    step_line(self.bookmark_selected_line, count, key.code);
+            }
+
+            KeyCode::Char('l') | KeyCode::Enter => {
+                if let Some((_, path)) = self.selected_bookmark() {
+                    self.showing_bookmarks = false;
+                    self.change_directory(path);
+                }
+            }
+
+            KeyCode::Char('e') => {
+                if let Some((_, path)) = self.selected_bookmark() {
+                    self.bookmark_path = path.to_string_lossy().into_owned();
+                    self.bookmark_error = None;
+                    self.editing_bookmark = true;
+                }
+            }
+
+            KeyCode::Char('d') => {
+                if let Some((letter, _)) = self.selected_bookmark() {
+                    self.state.marks.remove(&letter);
+                    self.save_marks();
+                    self.bookmark_selected_line = self
+                        .bookmark_selected_line
+                        .min(self.state.marks.len().saturating_sub(1));
+                }
+            }
+
+            _ => {}
+        }
+    }
+
+    /// Lists the worktrees of the selected repository, or of the current
+    /// directory when the selection is not one.
+    fn show_worktrees(&mut self) {
+        let repository = self
+            .selected_directory()
+            .filter(|dir| is_git_repository(dir))
+            .unwrap_or_else(|| self.current_directory.clone());
+
+        match list_worktrees(&repository) {
+            Ok(worktrees) => {
+                self.worktrees = worktrees;
+                self.worktree_selected_line = 0;
+                self.worktree_error = None;
+                self.creating_worktree = false;
+                self.showing_worktrees = true;
+            }
+            Err(e) => self.status_message = Some(e.to_string()),
+        }
+    }
+
+    /// The worktrees with a checkout, the ones the popup lists. A bare main
+    /// repository stays in `worktrees` since it names the repository and
+    /// new worktrees are added next to it.
+    pub fn checked_out_worktrees(&self) -> impl Iterator<Item = &GitWorktree> {
+        self.worktrees.iter().filter(|w| !w.bare)
+    }
+
+    fn selected_worktree(&self) -> Option<GitWorktree> {
+        self.checked_out_worktrees()
+            .nth(self.worktree_selected_line)
+            .cloned()
+    }
+
+    /// `repo/branch`, so worktrees of one repository sort together.
+    fn worktree_session_name(&self, worktree: &GitWorktree) -> String {
+        sanitize_session_name(&format!(
+            "{}/{}",
+            repository_name(&self.worktrees),
+            worktree.label()
+        ))
+    }
+
+    fn key_handler_worktrees(&mut self, key: KeyEvent) {
+        if self.creating_worktree {
+            match key.code {
+                KeyCode::Esc => {
+                    self.creating_worktree = false;
+                    self.worktree_error = None;
+                }
+
+                KeyCode::Char(c) if !c.is_control() && !c.is_whitespace() => {
+                    self.worktree_branch.push(c);
+                }
+
+                KeyCode::Backspace => {
+                    self.worktree_branch.pop();
+                }
+
+                KeyCode::Enter if self.worktree_branch.is_empty() => {
+                    self.worktree_error = Some("Branch name cannot be empty".to_string());
+                }
+
+                KeyCode::Enter if !self.tmux_usable() => {
+                    self.worktree_error = self.tmux_error.as_ref().map(|e| e.to_string());
+                }
+
+                KeyCode::Enter => match add_worktree(&self.worktrees, &self.worktree_branch) {
+                    Ok(path) => {
+                        let name = sanitize_session_name(&format!(
+                            "{}/{}",
+                            repository_name(&self.worktrees),
+                            self.worktree_branch
+                        ));
+                        self.showing_worktrees = false;
+                        self.creating_worktree = false;
+                        self.open_in_tmux_as(&path, &name);
+                    }
+                    Err(e) => self.worktree_error = Some(e.to_string()),
+                },
+
+                _ => {}
+            }
+            return;
+        }
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.showing_worktrees = false;
+            }
+
+            KeyCode::Char('j' | 'k') | KeyCode::Down | KeyCode::Up
+                if self.checked_out_worktrees().next().is_some() =>
+            {
+                self.worktree_selected_line = step_line(
+                    self.worktree_selected_line,
+                    self.checked_out_worktrees().count(),
+                    key.code,
+                );
+            }
+
+            KeyCode::Char('a') => {
+                self.worktree_branch.clear();
+                self.worktree_error = None;
+                self.creating_worktree = true;
+            }
+
+            KeyCode::Char('l') => {
+                if let Some(worktree) = self.selected_worktree() {
+                    self.showing_worktrees = false;
+                    self.change_directory(worktree.path);
+                }
+            }
+
+            KeyCode::Char('t') | KeyCode::Enter if !self.tmux_usable() => {
+                self.worktree_error = self.tmux_error.as_ref().map(|e| e.to_string());
+            }
+
+            KeyCode::Char('t') | KeyCode::Enter => {
+                if let Some(worktree) = self.selected_worktree() {
+                    let name = self.worktree_session_name(&worktree);
+                    self.showing_worktrees = false;
+                    self.open_in_tmux_as(&worktree.path, &name);
+                }
+            }
+
+            _ => {}
+        }
+    }
+
+    /// First of `name-2`, `name-3`, ... that no session uses yet.
+    fn free_session_name(&self, name: &str) -> String {
+        (2..)
+            .map(|n| format!("{}-{}", name, n))
+            .find(|candidate| !self.tmux_sessions.iter().any(|s| &s.name == candidate))
+            .expect("Ran out of session names")
+    }
+
+    fn key_handler_help(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Char('H') => {
+                self.show_help = !self.show_help;
+            }
+
+            KeyCode::Char('j' | 'k') | KeyCode::Down | KeyCode::Up if self.help_total_lines > 0 => {
+                move_selection(
+                    &mut self.help_selected_line,
+                    &mut self.help_scroll,
+                    self.help_total_lines,
+                    self.help_visible_height,
+                    key.code,
+                );
+            }
+
+            KeyCode::Char('1') => self.help_selected_tab = 0,
+            KeyCode::Char('2') => self.help_selected_tab = 1,
+            KeyCode::Char('3') => self.help_selected_tab = 2,
+            KeyCode::Char('4') => self.help_selected_tab = 3,
+            KeyCode::Char('5') => self.help_selected_tab = 4,
+            KeyCode::Char('6') => self.help_selected_tab = 5,
+
+            KeyCode::Char('h') => {
+                if self.help_selected_tab == 0 {
+                    self.help_selected_tab = 5;
+                } else {
+                    self.help_selected_tab -= 1;
+                }
+            }
+            KeyCode::Char('l') => {
+                if self.help_selected_tab == 5 {
+                    self.help_selected_tab = 0;
+                } else {
+                    self.help_selected_tab += 1;
+                }
+            }
+
+            _ => {}
+        }
+    }
+
+    fn key_handler_searching(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                self.searching = false;
+                self.searched_string.clear();
+                self.search_matches.clear();
+                self.search_match_index = None;
+            }
+
+            KeyCode::Char(c) if !c.is_control() && !c.is_whitespace() => {
+                self.searched_string.push(c);
+
+                self.last_search_update = Some(Instant::now());
+                self.update_search_results();
+            }
+
+            KeyCode::Backspace => {
+                self.searched_string.pop();
+                self.last_search_update = Some(Instant::now());
+                self.update_search_results();
+            }
+
+            KeyCode::Enter => {
+                self.last_search_update = Some(Instant::now());
+                self.searching = false;
+            }
+
+            _ => {}
+        }
+    }
+
+    fn update_search_results(&mut self) {
+        self.search_matches.clear();
+        self.search_match_index = None;
+
+        if self.searched_string.is_empty() {
+            return;
+        }
+
+        self.search_highlights.clear();
+        for (i, fuzzy) in rank_matches(&self.search_candidates(), &self.searched_string) {
+            self.search_matches.push(i);
+            self.search_highlights.insert(i, fuzzy.positions);
+        }
+
+        if !self.search_matches.is_empty() {
+            self.search_match_index = Some(0);
+            self.select_line(self.search_matches[0]);
+        }
+    }
+
+    /// The displayed names of the list the search runs on.
+    fn search_candidates(&self) -> Vec<String> {
+        match self.selected_tab {
+            1 => self.tmux_sessions.iter().map(|s| s.name.clone()).collect(),
+            2 => self.projects.iter().map(|p| display_path(p)).collect(),
+            3 => self
+                .recent
+                .iter()
+                .map(|entry| match entry.kind {
+                    EntryKind::Directory => display_path(Path::new(&entry.value)),
+                    EntryKind::Session => entry.value.clone(),
+                })
+                .collect(),
+            _ => self
+                .current_directory_contents
+                .iter()
+                .map(|path| {
+                    path.file_name()
+                        .and_then(|n| n.to_str())
+                        .unwrap_or_default()
+                        .to_string()
+                })
+                .collect(),
+        }
+    }
+
+    /// Switches tabs, dropping any search since it indexes the old list.
+    fn select_tab(&mut self, tab: usize) {
+        if self.selected_tab != tab {
+            self.search_matches.clear();
+            self.search_match_index = None;
+            self.searched_string.clear();
+        }
+        if tab == 3 {
+            self.refresh_recent();
+        }
+        self.selected_tab = tab;
+    }
+
+    fn refresh_recent(&mut self) {
+        self.recent = self.frecency.sorted();
+        self.recent_selected_line = self
+            .recent_selected_line
+            .min(self.recent.len().saturating_sub(1));
+    }
+
+    fn resume_tui(&mut self) {
+        tui::resume().expect("Could not resume the TUI");
+        self.needs_clear = true;
+    }
+
+    /// Suspends the TUI to pick from the current list with fzf, then acts on
+    /// the choice like `l` (directories) or `t` (projects, recent, sessions).
+    fn pick_externally(&mut self) {
+        let fzf = &self.config.fzf;
+        let preview = fzf.preview.as_deref().unwrap_or(DEFAULT_PREVIEW);
+        let session_preview = fzf
+            .session_preview
+            .as_deref()
+            .unwrap_or(DEFAULT_SESSION_PREVIEW);
+
+        // Kinds index into `[preview, session_preview]`.
+        let (candidates, previews): (Vec<(usize, String)>, &[&str]) = match self.selected_tab {
+            0 => (
+                self.current_directory_contents
+                    .iter()
+                    .map(|p| {
+                        (
+                            0,
+                            p.file_name().unwrap_or_default().to_string_lossy().into(),
+                        )
+                    })
+                    .collect(),
+                &[preview],
+            ),
+            1 => (
+                self.tmux_sessions
+                    .iter()
+                    .map(|s| (0, s.name.clone()))
+                    .collect(),
+                &[session_preview],
+            ),
+            2 => (
+                self.projects
+                    .iter()
+                    .map(|p| (0, p.to_string_lossy().into()))
+                    .collect(),
+                &[preview],
+            ),
+            _ => (
+                self.recent
+                    .iter()
+                    .map(|e| match e.kind {
+                        EntryKind::Directory => (0, e.value.clone()),
+                        EntryKind::Session => (1, e.value.clone()),
+                    })
+                    .collect(),
+                &[preview, session_preview],
+            ),
+        };
+
+        if candidates.is_empty() {
+            return;
+        }
+
+        self.leave_tui();
+        let picked = pick_with_fzf(
+            &self.config.fzf,
+            &candidates,
+            previews,
+            &self.current_directory,
+        );
+        self.resume_tui();
+
+        let index = match picked {
+            Ok(index) => index.filter(|&i| i < candidates.len()),
+            Err(e) => {
+                self.status_message = Some(e.to_string());
+                None
+            }
+        };
+        let Some(index) = index else {
+            return;
+        };
+
+        match self.selected_tab {
+            0 => {
+                self.directories_selected_line = index;
+                self.ensure_selected_visible();
+                if self.current_directory_contents[index].is_dir() {
+                    self.key_handler_directories(KeyEvent::from(KeyCode::Char('l')));
+                }
+            }
+            1 => {
+                self.tmux_selected_line = index;
+                self.key_handler_tmux(KeyEvent::from(KeyCode::Char('t')));
+            }
+            2 => {
+                self.projects_selected_line = index;
+                self.key_handler_projects(KeyEvent::from(KeyCode::Char('t')));
+            }
+            _ => {
+                self.recent_selected_line = index;
+                self.key_handler_recent(KeyEvent::from(KeyCode::Char('t')));
+            }
+        }
+    }
+
+    fn leave_tui(&self) {
+        tui::suspend().expect("Could not leave the TUI");
+    }
+
+    /// Records a visit in the frecency store. Failing to save only costs
+    /// history, so it is reported and otherwise ignored.
+    fn record_visit(&mut self, kind: EntryKind, value: &str) {
+        self.frecency.add(kind, value);
+        if let Err(e) = self.frecency.save() {
+            eprintln!("Could not save frecency store: {}", e);
+        }
+    }
+
+    /// With `--pick`, ends the run with `target` as the pick and returns
+    /// true. Everything that would open tmux asks this first.
+    fn pick_instead(&mut self, target: String) -> bool {
+        if self.pick {
+            self.picked = Some(target);
+            self.exit = true;
+        }
+        self.pick
+    }
+
+    /// Leaves the TUI and attaches to the session called `name`.
+    fn attach_session(&mut self, name: &str) {
+        if self.pick_instead(name.to_string()) {
+            return;
+        }
+
+        self.leave_tui();
+        self.record_visit(EntryKind::Session, name);
+
+        if let Err(e) = reenter_tmux_session(name) {
+            eprintln!("{}", e);
+        }
+
+        self.exit = true;
+    }
+
+    /// Leaves the TUI and opens `path` in a new tmux session with its
+    /// project environment.
+    fn open_in_tmux(&mut self, path: &Path) {
+        self.open_in_tmux_as(path, &session_name_for_path(path));
+    }
+
+    fn open_in_tmux_as(&mut self, path: &Path, session_name: &str) {
+        if self.pick_instead(path.to_string_lossy().into_owned()) {
+            return;
+        }
+
+        self.leave_tui();
+
+        if path.is_dir() {
+            self.record_visit(EntryKind::Directory, &path.to_string_lossy());
+
+            let (env, warnings) = project_environment(&self.config, path);
+
+            for warning in warnings {
+                eprintln!("{}", warning);
+            }
+
+            if let Err(e) = enter_in_tmux(session_name, path, &env) {
+                eprintln!("{}", e);
+            }
+        }
+
+        self.exit = true;
+    }
+
+    fn start_project_scan(&mut self) {
+        if self.config.project_roots.is_empty() {
+            return;
+        }
+
+        let roots = self.config.project_roots.clone();
+        let markers = self.config.project_markers.clone();
+        let (sender, receiver) = mpsc::channel();
+
+        thread::spawn(move || {
+            let _ = sender.send(discover_projects(&roots, &markers));
+        });
+
+        self.projects_receiver = Some(receiver);
+        self.projects_scanning = true;
+    }
+
+    fn poll_project_scan(&mut self) {
+        let Some(receiver) = &self.projects_receiver else {
+            return;
+        };
+
+        if let Ok(projects) = receiver.try_recv() {
+            self.projects = projects;
+            self.projects_scanning = false;
+            self.projects_receiver = None;
+            self.projects_selected_line = self
+                .projects_selected_line
+                .min(self.projects.len().saturating_sub(1));
+
+            if self.selected_tab == 2 {
+                self.search_matches.clear();
+                self.search_match_index = None;
+            }
+        }
+    }
+
+    /// Replaces any running status check, whose results would be for an
+    /// outdated listing.
+    fn start_git_status(&mut self) {
+        let paths = self.unfiltered_directory_contents.clone();
+        let (sender, receiver) = mpsc::channel();
+
+        thread::spawn(move || {
+            for status in git_statuses(paths) {
+                if sender.send(status).is_err() {
+                    break;
+                }
+            }
+        });
+
+        self.git_status_receiver = Some(receiver);
+    }
+
+    fn poll_git_status(&mut self) {
+        let Some(receiver) = &self.git_status_receiver else {
+            return;
+        };
+
+        loop {
+            match receiver.try_recv() {
+                Ok((path, status)) => {
+                    self.git_statuses.insert(path, status);
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.git_status_receiver = None;
+                    break;
+                }
+            }
+        }
+    }
+
+    fn refresh_tmux_sessions(&mut self) {
+        match list_tmux_sessions() {
+            Ok(sessions) => {
+                self.tmux_sessions = sessions;
+                self.tmux_error = None;
+            }
+            Err(e) => {
+                self.tmux_sessions = Vec::new();
+                self.tmux_error = Some(e);
+            }
+        }
+
+        self.tmux_version = tmux_version().ok().flatten();
+        if self.selected_tab == 1 {
+            // The matches index the old list.
+            self.search_matches.clear();
+            self.search_match_index = None;
+        }
+        self.tmux_selected_line = self
+            .tmux_selected_line
+            .min(self.tmux_sessions.len().saturating_sub(1));
+    }
+
+    /// A missing server is fine for `t`: `new-session` starts one. With
+    /// `--pick` nothing is opened, so tmux is not needed at all.
+    fn tmux_usable(&self) -> bool {
+        self.pick || matches!(self.tmux_error, None | Some(TmuxError::NoServer))
+    }
+
+    fn initalize_state(&mut self) {
+        self.current_directory = match self
+            .start_directory
+            .take()
+            .map_or_else(get_current_directory_name, Ok)
+        {
+            Ok(dir) => dir,
+            Err(e) => {
+                let home = env::var_os("HOME").map_or_else(|| PathBuf::from("/"), PathBuf::from);
+                self.status_message = Some(format!(
+                    "Could not get the current directory ({}), starting in {}",
+                    e,
+                    home.display()
+                ));
+                home
+            }
+        };
+        self.reload_directory();
+    }
+
+    /// The highlighted entry, if it is a directory.
+    fn selected_directory(&self) -> Option<PathBuf> {
+        self.current_directory_contents
+            .get(self.directories_selected_line)
+            .filter(|path| path.is_dir())
+            .cloned()
+    }
+
+    /// Moves to `path` and records the move in the back/forward history.
+    fn change_directory(&mut self, path: PathBuf) {
+        let previous = self.current_directory.clone();
+        if path != previous && self.visit_directory(path) {
+            push_history(&mut self.back_history, previous);
+            self.forward_history.clear();
+        }
+    }
+
+    /// Keeps the entry when it cannot be opened, so a later try still can.
+    fn go_back(&mut self) {
+        if let Some(path) = self.back_history.pop() {
+            let current = self.current_directory.clone();
+            if self.visit_directory(path.clone()) {
+                push_history(&mut self.forward_history, current);
+            } else {
+                self.back_history.push(path);
+            }
+        }
+    }
+
+    fn go_forward(&mut self) {
+        if let Some(path) = self.forward_history.pop() {
+            let current = self.current_directory.clone();
+            if self.visit_directory(path.clone()) {
+                push_history(&mut self.back_history, current);
+            } else {
+                self.forward_history.push(path);
+            }
+        }
+    }
+
+    /// Moves to `path`, dropping the search and filter of the old directory,
+    /// and highlights the directory we came up from, or else the entry last
+    /// selected there. Stays in the old directory when `path` cannot be read.
+    fn visit_directory(&mut self, path: PathBuf) -> bool {
+        let contents = match get_current_directory_contents(&path, &self.listing_filter()) {
+            Ok(contents) => contents,
+            Err(e) => {
+                self.status_message = Some(format!("Cannot open {}: {}", path.display(), e));
+                return false;
+            }
+        };
+
+        if let Some(selected) = self
+            .current_directory_contents
+            .get(self.directories_selected_line)
+        {
+            self.last_selected
+                .insert(self.current_directory.clone(), selected.clone());
+        }
+
+        let previous = std::mem::replace(&mut self.current_directory, path);
+        self.directory_filter.clear();
+        self.git_statuses.clear();
+        self.directory_error = None;
+        self.set_directory_contents(contents);
+
+        let came_from = previous
+            .ancestors()
+            .find(|dir| dir.parent() == Some(self.current_directory.as_path()));
+        let remembered = self.last_selected.get(&self.current_directory);
+        if let Some(line) = [came_from, remembered.map(PathBuf::as_path)]
+            .into_iter()
+            .flatten()
+            .find_map(|wanted| {
+                self.current_directory_contents
+                    .iter()
+                    .position(|entry| entry == wanted)
+            })
+        {
+            self.directories_selected_line = line;
+            if line >= self.directories_visible_height {
+                self.directories_scroll =
+                    line.saturating_sub(self.directories_visible_height.saturating_sub(1));
+            }
+        }
+
+        true
+    }
+
+    /// Re-reads the current directory and applies the filter. If it became
+    /// unreadable, the listing is emptied and the pane shows why.
+    fn reload_directory(&mut self) {
+        match get_current_directory_contents(&self.current_directory, &self.listing_filter()) {
+            Ok(contents) => {
+                self.directory_error = None;
+                self.set_directory_contents(contents);
+            }
+            Err(e) => {
+                self.directory_error = Some(e.to_string());
+                self.set_directory_contents(Vec::new());
+            }
+        }
+    }
+
+    fn set_directory_contents(&mut self, contents: Vec<PathBuf>) {
+        self.unfiltered_directory_contents = contents;
+        sort_entries(&mut self.unfiltered_directory_contents, &self.state.sort);
+        self.preview = None;
+        self.start_git_status();
+        self.directory_metadata = if self.state.show_metadata {
+            self.unfiltered_directory_contents
+                .iter()
+                .filter_map(|path| Some((path.clone(), read_metadata(path)?)))
+                .collect()
+        } else {
+            HashMap::new()
+        };
+        self.apply_directory_filter();
+    }
+
+    fn toggle_preview(&mut self) {
+        self.state.show_preview = !self.state.show_preview;
+        if let Err(e) = self.state.save() {
+            self.status_message = Some(format!("Could not save settings: {}", e));
+        }
+    }
+
+    /// What `--pick` prints for the active tab: a path, or a session name.
+    fn pick_target(&self) -> Option<String> {
+        match self.selected_tab {
+            1 => self
+                .tmux_sessions
+                .get(self.tmux_selected_line)
+                .map(|session| session.name.clone()),
+            2 => self
+                .projects
+                .get(self.projects_selected_line)
+                .map(|path| path.to_string_lossy().into_owned()),
+            3 => self
+                .recent
+                .get(self.recent_selected_line)
+                .map(|entry| entry.value.clone()),
+            _ => Some(
+                self.current_directory_contents
+                    .get(self.directories_selected_line)
+                    .unwrap_or(&self.current_directory)
+                    .to_string_lossy()
+                    .into_owned(),
+            ),
+        }
+    }
+
+    /// The entry the preview pane shows for the active tab.
+    fn preview_target(&self) -> Option<PathBuf> {
+        match self.selected_tab {
+            2 => self.projects.get(self.projects_selected_line).cloned(),
+            3 => self
+                .recent
+                .get(self.recent_selected_line)
+                .filter(|entry| entry.kind == EntryKind::Directory)
+                .map(|entry| PathBuf::from(&entry.value)),
+            _ => self
+                .current_directory_contents
+                .get(self.directories_selected_line)
+                .cloned(),
+        }
+    }
+
+    /// Preview of the selected entry, loaded when the selection changed.
+    pub fn current_preview(&mut self) -> Option<&(PathBuf, Preview)> {
+        let target = self.preview_target()?;
+        if self
+            .preview
+            .as_ref()
+            .is_none_or(|(path, _)| *path != target)
+        {
+            let preview = load_preview(&target);
+            self.preview = Some((target, preview));
+        }
+        self.preview.as_ref()
+    }
+
+    /// Re-sorts the listing, keeping the selected entry highlighted, and
+    /// remembers the order for the next run.
+    fn sort_changed(&mut self) {
+        self.reload_keeping_selection();
+
+        if let Err(e) = self.state.save() {
+            self.status_message = Some(format!("Could not save sort order: {}", e));
+        }
+    }
+
+    /// Reloads the listing and highlights the same entry again.
+    fn reload_keeping_selection(&mut self) {
+        let selected = self
+            .current_directory_contents
+            .get(self.directories_selected_line)
+            .cloned();
+        self.reload_directory();
+        if let Some(selected) = selected
+            && let Some(line) = self
+                .current_directory_contents
+                .iter()
+                .position(|path| *path == selected)
+        {
+            self.select_line(line);
+        }
+    }
+
+    fn listing_filter(&self) -> ListingFilter {
+        ListingFilter {
+            show_hidden: self.show_hidden,
+            respect_ignore: self.respect_ignore,
+            exclude: self.config.exclude.clone(),
+        }
+    }
+
+    /// Narrows `current_directory_contents` to the entries matching
+    /// `directory_filter`, keeping their order.
+    fn apply_directory_filter(&mut self) {
+        self.search_matches.clear();
+        self.search_match_index = None;
+        self.searched_string.clear();
+        self.filter_highlights.clear();
+
+        if self.directory_filter.is_empty() {
+            self.current_directory_contents = self.unfiltered_directory_contents.clone();
+        } else {
+            self.current_directory_contents = Vec::new();
+            for path in &self.unfiltered_directory_contents {
+                let name = path.file_name().unwrap_or_default().to_string_lossy();
+                if let Some(fuzzy) = fuzzy_match(&name, &self.directory_filter) {
+                    self.filter_highlights
+                        .insert(self.current_directory_contents.len(), fuzzy.positions);
+                    self.current_directory_contents.push(path.clone());
+                }
+            }
+        }
+
+        self.directories_selected_line = 0;
+        self.directories_scroll = 0;
+    }
+
+    fn start_search(&mut self) {
+        self.searching = true;
+        self.searched_string.clear();
+        self.search_matches.clear();
+        self.search_match_index = None;
+        self.last_search_update = Some(Instant::now());
+        self.update_search_results();
+    }
+
+    fn jump_to_next_match(&mut self) {
+        if self.search_matches.is_empty() {
+            return;
+        }
+
+        let next_index = match self.search_match_index {
+            Some(i) => (i + 1) % self.search_matches.len(),
+            None => 0,
+        };
+
+        self.search_match_index = Some(next_index);
+        self.select_line(self.search_matches[next_index]);
+    }
+
+    fn jump_to_prev_match(&mut self) {
+        if self.search_matches.is_empty() {
+            return;
+        }
+
+        let prev_index = match self.search_match_index {
+            Some(0) | None => self.search_matches.len() - 1,
+            Some(i) => i - 1,
+        };
+
+        self.search_match_index = Some(prev_index);
+        self.select_line(self.search_matches[prev_index]);
+    }
+
+    fn select_line(&mut self, line: usize) {
+        match self.selected_tab {
+            1 => self.tmux_selected_line = line,
+            2 => self.projects_selected_line = line,
+            3 => self.recent_selected_line = line,
+            _ => self.directories_selected_line = line,
+        }
+        self.ensure_selected_visible();
+    }
+
+    fn ensure_selected_visible(&mut self) {
+        let (selected, scroll, visible_height) = match self.selected_tab {
+            // Sessions span several lines; the tmux view scrolls to the
+            // selected one itself when drawn.
+            1 => return,
+            2 => (
+                self.projects_selected_line,
+                &mut self.projects_scroll,
+                self.projects_visible_height,
+            ),
+            3 => (
+                self.recent_selected_line,
+                &mut self.recent_scroll,
+                self.recent_visible_height,
+            ),
+            _ => (
+                self.directories_selected_line,
+                &mut self.directories_scroll,
+                self.directories_visible_height,
+            ),
+        };
+
+        scroll_to_line(selected, scroll, visible_height);
+    }
+}
+
+/// Pushes onto a back or forward stack, dropping the oldest entry beyond
+/// `MAX_HISTORY`.
+fn push_history(history: &mut Vec<PathBuf>, path: PathBuf) {
+    history.push(path);
+    if history.len() > MAX_HISTORY {
+        history.remove(0);
+    }
+}
+
+/// The line `j`/Down or `k`/Up moves to from `selected` in a list of `len`
+/// lines, wrapping around at both ends.
+fn step_line(selected: usize, len: usize, key: KeyCode) -> usize {
+    match key {
+        KeyCode::Char('k') | KeyCode::Up => selected.checked_sub(1).unwrap_or(len - 1),
+        _ => (selected + 1) % len,
+    }
+}
+
+/// Moves `selected` like [`step_line`] and scrolls so it stays within the
+/// `height` visible lines.
+fn move_selection(
+    selected: &mut usize,
+    scroll: &mut usize,
+    len: usize,
+    height: usize,
+    key: KeyCode,
+) {
+    *selected = step_line(*selected, len, key);
+    scroll_to_line(*selected, scroll, height);
+}
+
+/// Scrolls the least needed to show `selected` in `height` lines.
+fn scroll_to_line(selected: usize, scroll: &mut usize, height: usize) {
+    if selected < *scroll {
+        *scroll = selected;
+    } else if selected >= *scroll + height {
+        *scroll = selected.saturating_sub(height.saturating_sub(1));
+    }
 }
@@ -0,0 +1,152 @@
+use std::path::{Path, PathBuf};
+
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum, error::ErrorKind};
+
+use crate::paths::directories::expand_tilde;
+
+/// Browse directories and open them in tmux sessions.
+#[derive(Debug, Default, Parser)]
+#[command(version, about)]
+pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Directory to start in, the current directory by default
+    #[arg(value_parser = parse_directory)]
+    pub path: Option<PathBuf>,
+
+    /// Start on the Tmux tab
+    #[arg(short, long, conflicts_with = "search")]
+    pub tmux: bool,
+
+    /// Start typing a search in the directory listing
+    #[arg(short, long)]
+    pub search: bool,
+
+    /// Show hidden files
+    #[arg(short = 'a', long)]
+    pub hidden: bool,
+
+    /// Print the entry picked with Enter to stdout instead of opening it,
+    /// drawing on /dev/tty, e.g. cd "$(pinta --pick)". Exits with 130 when
+    /// quit without a pick
+    #[arg(short, long)]
+    pub pick: bool,
+
+    /// File that `c` writes the current directory to, for the shell
+    /// function from `pinta init` to cd into
+    #[arg(long, global = true, hide = true)]
+    pub cwd_file: Option<PathBuf>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Print a shell function that wraps pinta so `c` changes the shell's
+    /// directory, e.g. eval "$(pinta init zsh)"
+    Init { shell: Shell },
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+const POSIX_INIT: &str = r#"pinta() {
+    local cwd_file code
+    cwd_file="$(mktemp "${TMPDIR:-/tmp}/pinta-cwd.XXXXXX")" || return
+    command pinta --cwd-file "$cwd_file" "$@"
+    code=$?
+    if [ -s "$cwd_file" ]; then
+        cd -- "$(cat "$cwd_file")" || code=$?
+    fi
+    rm -f -- "$cwd_file"
+    return "$code"
+}
+"#;
+
+const FISH_INIT: &str = r#"function pinta
+    set -l cwd_file (mktemp (set -q TMPDIR; and echo $TMPDIR; or echo /tmp)/pinta-cwd.XXXXXX); or return
+    command pinta --cwd-file $cwd_file $argv
+    set -l code $status
+    if test -s $cwd_file
+        cd (cat $cwd_file); or set code $status
+    end
+    rm -f -- $cwd_file
+    return $code
+end
+"#;
+
+/// The wrapper function printed by `pinta init <shell>`.
+pub fn init_script(shell: Shell) -> &'static str {
+    match shell {
+        Shell::Bash | Shell::Zsh => POSIX_INIT,
+        Shell::Fish => FISH_INIT,
+    }
+}
+
+impl Args {
+    /// Rejects PATH and the browser options next to a subcommand. clap
+    /// cannot do it, since `--cwd-file` from the shell wrapper has to be
+    /// accepted with every subcommand.
+    pub fn check_subcommand(&self) -> Result<(), clap::Error> {
+        let browser_options =
+            self.path.is_some() || self.tmux || self.search || self.hidden || self.pick;
+        if self.command.is_some() && browser_options {
+            return Err(Args::command().error(
+                ErrorKind::ArgumentConflict,
+                "PATH and the browser options cannot be used with a subcommand",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Makes PATH absolute with `~` expanded. A path that is not a readable
+/// directory is a usage error, reported by clap before the TUI starts.
+fn parse_directory(path: &str) -> Result<PathBuf, String> {
+    let resolved = expand_tilde(Path::new(path))
+        .canonicalize()
+        .map_err(|e| e.to_string())?;
+    if !resolved.is_dir() {
+        return Err("not a directory".to_string());
+    }
+    Ok(resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_cwd_file_with_init() {
+        let args = Args::try_parse_from(["pinta", "--cwd-file", "f", "init", "zsh"]).unwrap();
+        assert!(matches!(
+            args.command,
+            Some(Command::Init { shell: Shell::Zsh })
+        ));
+        assert_eq!(args.cwd_file, Some(PathBuf::from("f")));
+        assert!(args.check_subcommand().is_ok());
+    }
+
+    #[test]
+    fn rejects_browser_options_with_init() {
+        for argv in [
+            ["pinta", "/", "init", "zsh"],
+            ["pinta", "--pick", "init", "zsh"],
+        ] {
+            let args = Args::try_parse_from(argv).unwrap();
+            assert_eq!(
+                args.check_subcommand().unwrap_err().kind(),
+                ErrorKind::ArgumentConflict
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_paths_that_are_not_directories() {
+        let error = Args::try_parse_from(["pinta", "/nonexistent/pinta"]).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::ValueValidation);
+    }
+}
@@ -0,0 +1,369 @@
+//! fzf-style fuzzy matching shared by every searchable list, and picking
+//! through an external `fzf` process.
+
+use std::{
+    fmt,
+    io::{ErrorKind, Write},
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use crate::config::settings::FzfConfig;
+
+const SCORE_MATCH: i32 = 16;
+const SCORE_GAP_START: i32 = -3;
+const SCORE_GAP_EXTENSION: i32 = -1;
+
+const BONUS_BOUNDARY_WHITE: i32 = 10;
+const BONUS_BOUNDARY_DELIMITER: i32 = 9;
+const BONUS_BOUNDARY: i32 = 8;
+const BONUS_NON_WORD: i32 = 8;
+const BONUS_CAMEL_123: i32 = 7;
+const BONUS_CONSECUTIVE: i32 = 4;
+const BONUS_FIRST_CHAR_MULTIPLIER: i32 = 2;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FuzzyMatch {
+    pub score: i32,
+    /// Char indices of the matched characters in the candidate.
+    pub positions: Vec<usize>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum CharClass {
+    White,
+    Delimiter,
+    NonWord,
+    Lower,
+    Upper,
+    Number,
+}
+
+fn char_class(c: char) -> CharClass {
+    if c.is_whitespace() {
+        CharClass::White
+    } else if c == '/' || c == '\\' {
+        CharClass::Delimiter
+    } else if c.is_lowercase() {
+        CharClass::Lower
+    } else if c.is_uppercase() {
+        CharClass::Upper
+    } else if c.is_numeric() {
+        CharClass::Number
+    } else if c.is_alphanumeric() {
+        CharClass::Lower
+    } else {
+        CharClass::NonWord
+    }
+}
+
+/// Bonus for matching a char of class `class` right after one of `prev`.
+fn bonus_for(prev: CharClass, class: CharClass) -> i32 {
+    match (prev, class) {
+        (_, CharClass::White | CharClass::Delimiter | CharClass::NonWord) => BONUS_NON_WORD,
+        (CharClass::White, _) => BONUS_BOUNDARY_WHITE,
+        (CharClass::Delimiter, _) => BONUS_BOUNDARY_DELIMITER,
+        (CharClass::NonWord, _) => BONUS_BOUNDARY,
+        (CharClass::Lower, CharClass::Upper) => BONUS_CAMEL_123,
+        (CharClass::Lower | CharClass::Upper, CharClass::Number) => BONUS_CAMEL_123,
+        _ => 0,
+    }
+}
+
+/// Scores `candidate` against `query` like fzf's v2 algorithm: every query
+/// char must appear in order, and the best-scoring alignment wins. Matching
+/// is smart-case: case-sensitive only if the query has an uppercase char.
+pub fn fuzzy_match(candidate: &str, query: &str) -> Option<FuzzyMatch> {
+    let case_sensitive = query.chars().any(char::is_uppercase);
+    let normalize = |c: char| {
+        if case_sensitive {
+            c
+        } else {
+            c.to_lowercase().next().unwrap_or(c)
+        }
+    };
+
+    // Most candidates do not contain the query at all, so check that the
+    // query chars appear in order before building the score matrices.
+    let mut pending = query.chars().map(normalize).peekable();
+    for c in candidate.chars().map(normalize) {
+        if pending.peek() == Some(&c) {
+            pending.next();
+        }
+    }
+    if pending.peek().is_some() {
+        return None;
+    }
+
+    let query: Vec<char> = query.chars().map(normalize).collect();
+    let text: Vec<char> = candidate.chars().collect();
+    let lowered: Vec<char> = text.iter().copied().map(normalize).collect();
+
+    if query.is_empty() {
+        return Some(FuzzyMatch {
+            score: 0,
+            positions: Vec::new(),
+        });
+    }
+    if query.len() > text.len() {
+        return None;
+    }
+
+    let mut prev_class = CharClass::White;
+    let bonuses: Vec<i32> = text
+        .iter()
+        .map(|&c| {
+            let class = char_class(c);
+            let bonus = bonus_for(prev_class, class);
+            prev_class = class;
+            bonus
+        })
+        .collect();
+
+    let (m, n) = (query.len(), text.len());
+    // score[i][j]: best score with query[i] matched at text[j].
+    let mut score = vec![vec![None::<i32>; n]; m];
+    // Bonus of the consecutive run ending at (i, j).
+    let mut run_bonus = vec![vec![0; n]; m];
+    // Where query[i - 1] was matched for the best score at (i, j).
+    let mut from = vec![vec![0usize; n]; m];
+
+    for i in 0..m {
+        // Best of score[i - 1][k] - GAP_EXTENSION * k over k <= j - 2.
+        let mut best_gap: Option<(i32, usize)> = None;
+
+        for j in i..n {
+            if i > 0 && j >= 2 {
+                let k = j - 2;
+                if let Some(s) = score[i - 1][k] {
+                    let value = s - SCORE_GAP_EXTENSION * k as i32;
+                    if best_gap.is_none_or(|(best, _)| value > best) {
+                        best_gap = Some((value, k));
+                    }
+                }
+            }
+
+            if lowered[j] != query[i] {
+                continue;
+            }
+
+            let bonus = bonuses[j];
+
+            if i == 0 {
+                score[i][j] = Some(SCORE_MATCH + bonus * BONUS_FIRST_CHAR_MULTIPLIER);
+                run_bonus[i][j] = bonus;
+                continue;
+            }
+
+            let consecutive = score[i - 1][j - 1].map(|s| {
+                let run = run_bonus[i - 1][j - 1].max(BONUS_CONSECUTIVE);
+                (s + SCORE_MATCH + bonus.max(run), run.max(bonus))
+            });
+            let gapped = best_gap.map(|(value, k)| {
+                let gap = SCORE_GAP_START + SCORE_GAP_EXTENSION * (j as i32 - 2);
+                (value + gap + SCORE_MATCH + bonus, k)
+            });
+
+            match (consecutive, gapped) {
+                (Some((c, run)), Some((g, _))) if c >= g => {
+                    score[i][j] = Some(c);
+                    run_bonus[i][j] = run;
+                    from[i][j] = j - 1;
+                }
+                (Some((c, run)), None) => {
+                    score[i][j] = Some(c);
+                    run_bonus[i][j] = run;
+                    from[i][j] = j - 1;
+                }
+                (_, Some((g, k))) => {
+                    score[i][j] = Some(g);
+                    run_bonus[i][j] = bonus;
+                    from[i][j] = k;
+                }
+                (None, None) => {}
+            }
+        }
+    }
+
+    let (end, best) = (0..n)
+        .filter_map(|j| score[m - 1][j].map(|s| (j, s)))
+        .max_by_key(|&(j, s)| (s, std::cmp::Reverse(j)))?;
+
+    let mut positions = vec![end; m];
+    for i in (1..m).rev() {
+        positions[i - 1] = from[i][positions[i]];
+    }
+
+    Some(FuzzyMatch {
+        score: best,
+        positions,
+    })
+}
+
+/// Matches `query` against every candidate and returns `(index, match)`
+/// pairs, best first. Ties go to the shorter candidate, then list order.
+pub fn rank_matches<S: AsRef<str>>(candidates: &[S], query: &str) -> Vec<(usize, FuzzyMatch)> {
+    let mut matches: Vec<(usize, FuzzyMatch)> = candidates
+        .iter()
+        .enumerate()
+        .filter_map(|(i, c)| fuzzy_match(c.as_ref(), query).map(|m| (i, m)))
+        .collect();
+
+    matches.sort_by_key(|(i, m)| {
+        (
+            std::cmp::Reverse(m.score),
+            candidates[*i].as_ref().chars().count(),
+            *i,
+        )
+    });
+    matches
+}
+
+pub const DEFAULT_PREVIEW: &str = "ls -la {}";
+pub const DEFAULT_SESSION_PREVIEW: &str = "tmux capture-pane -ep -t {}";
+
+#[derive(Debug)]
+pub enum FzfError {
+    NotInstalled(String),
+    Failed(String),
+}
+
+impl fmt::Display for FzfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FzfError::NotInstalled(command) => write!(f, "{} is not installed", command),
+            FzfError::Failed(msg) => write!(f, "fzf failed: {}", msg),
+        }
+    }
+}
+
+/// Pipes `candidates` into fzf, run from `dir`, and returns the index of the
+/// chosen one. Each candidate is a `(kind, text)` pair, and `previews[kind]`
+/// is the `--preview` used for it, with `{}` standing for the text. Returns
+/// `None` if the user aborted or nothing matched. fzf draws on `/dev/tty`,
+/// so the TUI must be suspended.
+pub fn pick_with_fzf(
+    config: &FzfConfig,
+    candidates: &[(usize, String)],
+    previews: &[&str],
+    dir: &Path,
+) -> Result<Option<usize>, FzfError> {
+    let command = config.command.as_deref().unwrap_or("fzf");
+
+    // Lines are `index\tkind\ttext` with only the text shown and searched,
+    // so the pick maps back to its index even when texts repeat.
+    let mut cmd = Command::new(command);
+    cmd.args(["--delimiter", "\t", "--with-nth", "3.."])
+        .args(&config.options)
+        .current_dir(dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit());
+
+    match previews {
+        [] => {}
+        [preview] => {
+            cmd.args(["--preview", &preview.replace("{}", "{3..}")]);
+        }
+        previews => {
+            let arms: String = previews
+                .iter()
+                .enumerate()
+                .map(|(kind, preview)| format!("{}) {} ;; ", kind, preview.replace("{}", "{3..}")))
+                .collect();
+            // The dispatch is sh syntax, so it must not run in e.g. fish.
+            cmd.env("SHELL", "sh")
+                .args(["--preview", &format!("case {{2}} in {}esac", arms)]);
+        }
+    }
+
+    let mut child = cmd.spawn().map_err(|e| match e.kind() {
+        ErrorKind::NotFound => FzfError::NotInstalled(command.to_string()),
+        _ => FzfError::Failed(e.to_string()),
+    })?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        let lines: Vec<String> = candidates
+            .iter()
+            .enumerate()
+            .map(|(i, (kind, text))| format!("{}\t{}\t{}", i, kind, text))
+            .collect();
+        // fzf may exit before reading everything, so a broken pipe is fine.
+        let _ = stdin.write_all(lines.join("\n").as_bytes());
+    }
+
+    let output = child
+        .wait_with_output()
+        .map_err(|e| FzfError::Failed(e.to_string()))?;
+
+    match output.status.code() {
+        Some(0) => Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .next()
+            .and_then(|line| line.split('\t').next()?.parse().ok())),
+        // 1: no match, 130: aborted with Esc or Ctrl-C
+        Some(1) | Some(130) => Ok(None),
+        _ => Err(FzfError::Failed(format!("exited with {}", output.status))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn positions(candidate: &str, query: &str) -> Option<Vec<usize>> {
+        fuzzy_match(candidate, query).map(|m| m.positions)
+    }
+
+    #[test]
+    fn matches_query_chars_in_order() {
+        assert_eq!(positions("pinta", "pna"), Some(vec![0, 2, 4]));
+        assert_eq!(positions("pinta", "ap"), None);
+        assert_eq!(positions("pi", "pinta"), None);
+    }
+
+    #[test]
+    fn empty_query_matches_everything() {
+        assert_eq!(
+            fuzzy_match("anything", ""),
+            Some(FuzzyMatch {
+                score: 0,
+                positions: Vec::new(),
+            })
+        );
+    }
+
+    #[test]
+    fn is_smart_case() {
+        assert!(fuzzy_match("README.md", "readme").is_some());
+        assert!(fuzzy_match("README.md", "ReadMe").is_none());
+        assert!(fuzzy_match("ReadMe.md", "ReadMe").is_some());
+    }
+
+    #[test]
+    fn prefers_word_boundaries() {
+        assert_eq!(positions("src/app/app.rs", "ar"), Some(vec![8, 12]));
+        assert_eq!(positions("some_config", "sc"), Some(vec![0, 5]));
+
+        let boundary = fuzzy_match("foo/bar", "bar").unwrap();
+        let inside = fuzzy_match("foobar", "bar").unwrap();
+        assert!(boundary.score > inside.score);
+    }
+
+    #[test]
+    fn positions_are_char_indices() {
+        assert_eq!(positions("häuser/öl", "öl"), Some(vec![7, 8]));
+        assert_eq!(positions("日本語のファイル", "ファ"), Some(vec![4, 5]));
+    }
+
+    #[test]
+    fn ranks_best_matches_first() {
+        let candidates = ["xaxbxc", "abc", "a_b_c", "abcdef"];
+        let order: Vec<usize> = rank_matches(&candidates, "abc")
+            .into_iter()
+            .map(|(i, _)| i)
+            .collect();
+
+        assert_eq!(order, [1, 3, 2, 0]);
+    }
+}
@@ -0,0 +1,366 @@
+use std::{
+    fmt,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GitError {
+    NotInstalled,
+    Failed(String),
+}
+
+impl fmt::Display for GitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GitError::NotInstalled => write!(f, "git is not installed or not in PATH"),
+            GitError::Failed(msg) => write!(f, "git failed: {}", msg),
+        }
+    }
+}
+
+/// Branch and working tree summary shown next to repositories.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GitStatus {
+    /// Branch name, or the short commit hash when detached.
+    pub branch: String,
+    pub dirty: bool,
+    pub ahead: u32,
+    pub behind: u32,
+    pub stashes: u32,
+}
+
+impl fmt::Display for GitStatus {
+    /// `main* ↑1 ↓2 ≡3`, only showing the parts that are non-zero.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.branch)?;
+        if self.dirty {
+            write!(f, "*")?;
+        }
+        if self.ahead > 0 {
+            write!(f, " ↑{}", self.ahead)?;
+        }
+        if self.behind > 0 {
+            write!(f, " ↓{}", self.behind)?;
+        }
+        if self.stashes > 0 {
+            write!(f, " ≡{}", self.stashes)?;
+        }
+        Ok(())
+    }
+}
+
+fn run_git(dir: &Path, args: &[&str]) -> Result<String, GitError> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        // Status refreshes the index otherwise, racing with git commands
+        // run by the user in that repository.
+        .env("GIT_OPTIONAL_LOCKS", "0")
+        .output()
+        .map_err(|e| {
+            if e.kind() == ErrorKind::NotFound {
+                GitError::NotInstalled
+            } else {
+                GitError::Failed(e.to_string())
+            }
+        })?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        // Progress lines come first, the reason for failing last.
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(GitError::Failed(
+            stderr.trim().lines().last().unwrap_or_default().to_string(),
+        ))
+    }
+}
+
+/// A directory with its own `.git` (a directory, or a file for worktrees
+/// and submodules), so nested folders of a repository are not reported.
+pub fn is_git_repository(dir: &Path) -> bool {
+    dir.join(".git").exists()
+}
+
+/// Runs `git status` and counts stashes, `None` if `dir` is not a
+/// repository or git cannot be run.
+pub fn git_status(dir: &Path) -> Option<GitStatus> {
+    let output = run_git(dir, &["status", "--porcelain=v2", "--branch"]).ok()?;
+    let mut status = parse_status_porcelain(&output);
+
+    // Fails when there is no stash, which just means zero.
+    status.stashes = run_git(
+        dir,
+        &["rev-list", "--walk-reflogs", "--count", "refs/stash"],
+    )
+    .ok()
+    .and_then(|count| count.trim().parse().ok())
+    .unwrap_or(0);
+
+    Some(status)
+}
+
+/// Parses `git status --porcelain=v2 --branch`.
+pub fn parse_status_porcelain(output: &str) -> GitStatus {
+    let mut status = GitStatus::default();
+    let mut oid = "";
+
+    for line in output.lines() {
+        if let Some(header) = line.strip_prefix("# ") {
+            let mut parts = header.split_whitespace();
+            match parts.next() {
+                Some("branch.oid") => oid = parts.next().unwrap_or_default(),
+                Some("branch.head") => status.branch = parts.next().unwrap_or_default().into(),
+                Some("branch.ab") => {
+                    for count in parts {
+                        if let Some(ahead) = count.strip_prefix('+') {
+                            status.ahead = ahead.parse().unwrap_or(0);
+                        } else if let Some(behind) = count.strip_prefix('-') {
+                            status.behind = behind.parse().unwrap_or(0);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        } else if !line.is_empty() {
+            status.dirty = true;
+        }
+    }
+
+    if status.branch == "(detached)" {
+        status.branch = oid.chars().take(7).collect();
+    }
+
+    status
+}
+
+/// Statuses of the repositories among `paths`, one at a time so a large
+/// listing does not start dozens of git processes at once.
+pub fn git_statuses(paths: Vec<PathBuf>) -> impl Iterator<Item = (PathBuf, GitStatus)> {
+    paths
+        .into_iter()
+        .filter(|path| is_git_repository(path))
+        .filter_map(|path| {
+            let status = git_status(&path)?;
+            Some((path, status))
+        })
+}
+
+/// One entry of `git worktree list --porcelain`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GitWorktree {
+    pub path: PathBuf,
+    /// Short branch name, `None` when detached or bare.
+    pub branch: Option<String>,
+    pub head: String,
+    pub bare: bool,
+}
+
+impl GitWorktree {
+    /// Branch name, or the short commit hash when detached.
+    pub fn label(&self) -> String {
+        match &self.branch {
+            Some(branch) => branch.clone(),
+            None => self.head.chars().take(7).collect(),
+        }
+    }
+}
+
+/// Worktrees of the repository containing `dir`, the main one first.
+pub fn list_worktrees(dir: &Path) -> Result<Vec<GitWorktree>, GitError> {
+    let output = run_git(dir, &["worktree", "list", "--porcelain"])?;
+    Ok(parse_worktree_porcelain(&output))
+}
+
+pub fn parse_worktree_porcelain(output: &str) -> Vec<GitWorktree> {
+    let mut worktrees: Vec<GitWorktree> = Vec::new();
+
+    for line in output.lines() {
+        let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+        if key == "worktree" {
+            worktrees.push(GitWorktree {
+                path: PathBuf::from(value),
+                branch: None,
+                head: String::new(),
+                bare: false,
+            });
+            continue;
+        }
+
+        let Some(worktree) = worktrees.last_mut() else {
+            continue;
+        };
+        match key {
+            "HEAD" => worktree.head = value.to_string(),
+            "branch" => {
+                worktree.branch = Some(
+                    value
+                        .strip_prefix("refs/heads/")
+                        .unwrap_or(value)
+                        .to_string(),
+                )
+            }
+            "bare" => worktree.bare = true,
+            _ => {}
+        }
+    }
+
+    worktrees
+}
+
+/// Name of the repository the worktrees belong to, taken from the main
+/// worktree so every linked worktree gets the same prefix.
+pub fn repository_name(worktrees: &[GitWorktree]) -> String {
+    worktrees
+        .first()
+        .and_then(|main| main.path.file_name())
+        .map(|name| {
+            let name = name.to_string_lossy();
+            name.strip_suffix(".git").unwrap_or(&name).to_string()
+        })
+        .unwrap_or_default()
+}
+
+/// Checks `branch` out into a new worktree next to the main one, named
+/// `<repo>-<branch>`. Existing local or remote branches are checked out,
+/// anything else is created from `HEAD`.
+pub fn add_worktree(worktrees: &[GitWorktree], branch: &str) -> Result<PathBuf, GitError> {
+    let main = worktrees
+        .first()
+        .ok_or_else(|| GitError::Failed("no worktrees found".to_string()))?;
+    let parent = main.path.parent().unwrap_or(&main.path);
+    let path = parent.join(format!(
+        "{}-{}",
+        repository_name(worktrees),
+        branch.replace('/', "-")
+    ));
+    let path_arg = path.to_string_lossy();
+
+    let local = run_git(
+        &main.path,
+        &[
+            "rev-parse",
+            "--verify",
+            "--quiet",
+            &format!("refs/heads/{}", branch),
+        ],
+    )
+    .is_ok();
+    let remote = run_git(
+        &main.path,
+        &[
+            "for-each-ref",
+            "--format=%(refname)",
+            &format!("refs/remotes/*/{}", branch),
+        ],
+    )
+    .is_ok_and(|refs| !refs.trim().is_empty());
+
+    if local || remote {
+        run_git(&main.path, &["worktree", "add", &path_arg, branch])?;
+    } else {
+        run_git(&main.path, &["worktree", "add", "-b", branch, &path_arg])?;
+    }
+
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_clean_branch_with_upstream() {
+        let output = "\
+# branch.oid 3f2a1b0c9d8e7f6a5b4c3d2e1f0a9b8c7d6e5f4a
+# branch.head main
+# branch.upstream origin/main
+# branch.ab +2 -5
+";
+
+        assert_eq!(
+            parse_status_porcelain(output),
+            GitStatus {
+                branch: "main".to_string(),
+                dirty: false,
+                ahead: 2,
+                behind: 5,
+                stashes: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn renamed_unmerged_and_untracked_entries_are_dirty() {
+        let headers = "# branch.oid 3f2a1b0c9d8e7f6a5b4c3d2e1f0a9b8c7d6e5f4a\n# branch.head main\n";
+        let entries = [
+            "1 .M N... 100644 100644 100644 3f2a1b0 3f2a1b0 src/main.rs",
+            "2 R. N... 100644 100644 100644 3f2a1b0 3f2a1b0 R100 new name.rs\told name.rs",
+            "u UU N... 100644 100644 100644 100644 3f2a1b0 4e3b2c1 5d4c3b2 conflict.rs",
+            "? untracked.txt",
+        ];
+
+        for entry in entries {
+            let status = parse_status_porcelain(&format!("{}{}\n", headers, entry));
+            assert!(status.dirty, "{}", entry);
+            assert_eq!(status.branch, "main");
+        }
+    }
+
+    #[test]
+    fn detached_head_shows_short_hash() {
+        let output = "\
+# branch.oid 3f2a1b0c9d8e7f6a5b4c3d2e1f0a9b8c7d6e5f4a
+# branch.head (detached)
+";
+
+        assert_eq!(parse_status_porcelain(output).branch, "3f2a1b0");
+    }
+
+    #[test]
+    fn parses_worktree_list() {
+        let output = "\
+worktree /home/me/pinta
+HEAD 3f2a1b0c9d8e7f6a5b4c3d2e1f0a9b8c7d6e5f4a
+branch refs/heads/main
+
+worktree /home/me/pinta-feature/x
+HEAD 4e3b2c1d0e9f8a7b6c5d4e3f2a1b0c9d8e7f6a5b
+branch refs/heads/feature/x
+
+worktree /home/me/pinta-detached
+HEAD 5d4c3b2a1f0e9d8c7b6a5f4e3d2c1b0a9f8e7d6c
+detached
+locked reason with spaces
+";
+
+        let worktrees = parse_worktree_porcelain(output);
+        assert_eq!(worktrees.len(), 3);
+        assert_eq!(worktrees[0].path, Path::new("/home/me/pinta"));
+        assert_eq!(worktrees[0].branch.as_deref(), Some("main"));
+        assert_eq!(worktrees[1].label(), "feature/x");
+        assert_eq!(worktrees[2].branch, None);
+        assert_eq!(worktrees[2].label(), "5d4c3b2");
+        assert_eq!(repository_name(&worktrees), "pinta");
+    }
+
+    #[test]
+    fn parses_bare_main_worktree() {
+        let output = "\
+worktree /srv/pinta.git
+bare
+
+worktree /srv/pinta-main
+HEAD 3f2a1b0c9d8e7f6a5b4c3d2e1f0a9b8c7d6e5f4a
+branch refs/heads/main
+";
+
+        let worktrees = parse_worktree_porcelain(output);
+        assert!(worktrees[0].bare);
+        assert!(!worktrees[1].bare);
+        assert_eq!(repository_name(&worktrees), "pinta");
+    }
+}
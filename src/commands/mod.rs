@@ -1 +1,3 @@
+pub mod fzf;
+pub mod git;
 pub mod tmux;
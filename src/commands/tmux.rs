@@ -1,14 +1,119 @@
-use std::io::Read;
+use std::env;
+use std::fmt;
+use std::io::ErrorKind;
+use std::path::Path;
 use std::process::Command;
-use std::{path::Path, process::Stdio};
+use std::sync::OnceLock;
 
 use chrono::{DateTime, Local, Utc};
 
+/// Oldest tmux release whose `-F` formats Pinta relies on.
+pub const MIN_TMUX_VERSION: TmuxVersion = TmuxVersion { major: 1, minor: 8 };
+
+/// Parsed `tmux -V`, filled on the first successful call to [`tmux_version`].
+static TMUX_VERSION: OnceLock<Option<TmuxVersion>> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TmuxVersion {
+    pub major: u32,
+    pub minor: u32,
+}
+
+impl fmt::Display for TmuxVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+/// tmux features that only exist from a given release onwards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TmuxFeature {
+    /// `new-session -c <dir>`
+    StartDirectory,
+    /// The `exit-empty` server option
+    ExitEmpty,
+    /// `new-session -e KEY=VALUE`
+    SessionEnvironment,
+}
+
+impl TmuxFeature {
+    pub fn min_version(self) -> TmuxVersion {
+        match self {
+            TmuxFeature::StartDirectory => TmuxVersion { major: 1, minor: 9 },
+            TmuxFeature::ExitEmpty => TmuxVersion { major: 2, minor: 7 },
+            TmuxFeature::SessionEnvironment => TmuxVersion { major: 3, minor: 2 },
+        }
+    }
+}
+
+impl fmt::Display for TmuxFeature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TmuxFeature::StartDirectory => write!(f, "new-session -c"),
+            TmuxFeature::ExitEmpty => write!(f, "exit-empty"),
+            TmuxFeature::SessionEnvironment => write!(f, "new-session -e"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TmuxError {
+    NotInstalled,
+    NoServer,
+    PermissionDenied(String),
+    VersionTooOld(TmuxVersion),
+    Unsupported(TmuxFeature, TmuxVersion),
+    Failed(String),
+}
+
+impl fmt::Display for TmuxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TmuxError::NotInstalled => write!(f, "tmux is not installed or not in $PATH"),
+            TmuxError::NoServer => write!(f, "No tmux server is running"),
+            TmuxError::PermissionDenied(msg) => {
+                write!(f, "Permission denied on the tmux socket: {}", msg)
+            }
+            TmuxError::VersionTooOld(version) => write!(
+                f,
+                "tmux {} is too old, Pinta needs at least {}",
+                version, MIN_TMUX_VERSION
+            ),
+            TmuxError::Unsupported(feature, version) => write!(
+                f,
+                "{} needs tmux {} or newer, found {}",
+                feature,
+                feature.min_version(),
+                version
+            ),
+            TmuxError::Failed(msg) => write!(f, "tmux failed: {}", msg),
+        }
+    }
+}
+
 #[derive(Default, Debug)]
 pub struct TmuxWindow {
     pub index: String,
     pub name: String,
     pub active: bool,
+    pub layout: Option<WindowLayout>,
+}
+
+/// A pane's cell in a window, in tmux cell coordinates.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PaneGeometry {
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+    pub height: u16,
+}
+
+/// A window's size and the geometry of its panes, from `#{window_layout}`.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct WindowLayout {
+    pub width: u16,
+    pub height: u16,
+    pub panes: Vec<PaneGeometry>,
 }
 
 #[derive(Default, Debug)]
@@ -18,14 +123,120 @@ pub struct TmuxSession {
     pub windows: Vec<TmuxWindow>,
 }
 
-pub fn reenter_tmux_session(session_name: &str) {
+/// Runs tmux with `args` and returns its stdout, classifying failures.
+fn run_tmux(args: &[&str]) -> Result<String, TmuxError> {
+    let output = Command::new("tmux").args(args).output().map_err(|e| {
+        if e.kind() == ErrorKind::NotFound {
+            TmuxError::NotInstalled
+        } else {
+            TmuxError::Failed(e.to_string())
+        }
+    })?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        Err(classify_tmux_stderr(&String::from_utf8_lossy(
+            &output.stderr,
+        )))
+    }
+}
+
+fn classify_tmux_stderr(stderr: &str) -> TmuxError {
+    let msg = stderr.trim();
+
+    if msg.contains("no server running")
+        || msg.contains("failed to connect to server")
+        || (msg.contains("error connecting to") && msg.contains("No such file or directory"))
+        || msg.contains("Connection refused")
+    {
+        TmuxError::NoServer
+    } else if msg.contains("Permission denied") {
+        TmuxError::PermissionDenied(msg.to_string())
+    } else {
+        TmuxError::Failed(msg.to_string())
+    }
+}
+
+/// Parses `tmux -V` output such as `tmux 3.3a`, `tmux next-3.5` or
+/// `tmux openbsd-7.4`.
+pub fn parse_tmux_version(output: &str) -> Option<TmuxVersion> {
+    let version = output.trim().strip_prefix("tmux ")?;
+    let version = version.rsplit('-').next()?;
+    let (major, rest) = version.split_once('.')?;
+    let minor: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+
+    Some(TmuxVersion {
+        major: major.parse().ok()?,
+        minor: minor.parse().ok()?,
+    })
+}
+
+/// Returns the installed tmux version, running `tmux -V` only once.
+/// `None` means the version string could not be parsed.
+pub fn tmux_version() -> Result<Option<TmuxVersion>, TmuxError> {
+    if let Some(version) = TMUX_VERSION.get() {
+        return Ok(*version);
+    }
+
+    let version = parse_tmux_version(&run_tmux(&["-V"])?);
+    Ok(*TMUX_VERSION.get_or_init(|| version))
+}
+
+/// Checks that tmux is installed and recent enough. Unparseable versions
+/// (e.g. builds from master) are assumed to be new enough.
+pub fn check_tmux_version() -> Result<Option<TmuxVersion>, TmuxError> {
+    match tmux_version()? {
+        Some(v) if v < MIN_TMUX_VERSION => Err(TmuxError::VersionTooOld(v)),
+        version => Ok(version),
+    }
+}
+
+pub fn require_tmux_feature(feature: TmuxFeature) -> Result<(), TmuxError> {
+    match tmux_version()? {
+        Some(v) if v < feature.min_version() => Err(TmuxError::Unsupported(feature, v)),
+        _ => Ok(()),
+    }
+}
+
+pub fn tmux_supports(feature: TmuxFeature) -> bool {
+    require_tmux_feature(feature).is_ok()
+}
+
+/// Starts a tmux server that stays alive without any sessions.
+///
+/// This turns `exit-empty` off on the new server only; it is only called when
+/// no server is running, so no existing server is reconfigured, and the
+/// setting is gone once that server exits.
+pub fn start_tmux_server() -> Result<(), TmuxError> {
+    require_tmux_feature(TmuxFeature::ExitEmpty)?;
+    run_tmux(&["start-server", ";", "set-option", "-s", "exit-empty", "off"]).map(|_| ())
+}
+
+/// Attaches to `session_name`, or switches the client to it when Pinta runs
+/// inside tmux, where attaching would nest sessions.
+pub fn reenter_tmux_session(session_name: &str) -> Result<(), TmuxError> {
+    let command = if env::var_os("TMUX").is_some() {
+        "switch-client"
+    } else {
+        "attach-session"
+    };
+    // `=` matches the name exactly instead of as a prefix.
     let status = Command::new("tmux")
-        .args(&["attach-session", "-t", session_name])
+        .args([command, "-t", &format!("={}", session_name)])
         .status()
-        .expect("Failed to attach to tmux session");
+        .map_err(|e| match e.kind() {
+            ErrorKind::NotFound => TmuxError::NotInstalled,
+            _ => TmuxError::Failed(e.to_string()),
+        })?;
 
-    if !status.success() {
-        eprintln!("Failed to attach to session: {}", session_name);
+    if status.success() {
+        Ok(())
+    } else {
+        Err(TmuxError::Failed(format!(
+            "Failed to attach to session: {}",
+            session_name
+        )))
     }
 }
 
@@ -43,52 +254,261 @@ pub fn parse_tmux_timestamp(ts: &str) -> String {
     }
 }
 
-pub fn enter_in_tmux(path: &Path) {
-    let session_name = path
+/// Creates a detached session named `session_name` in `path` with `env`
+/// set for its processes.
+pub fn create_tmux_session(
+    session_name: &str,
+    path: &Path,
+    env: &[(String, String)],
+) -> Result<(), TmuxError> {
+    let path_str = path
+        .to_str()
+        .ok_or_else(|| TmuxError::Failed(format!("Path is not valid UTF-8: {}", path.display())))?;
+
+    // tmux before 1.9 has no -c, so fall back to cd-ing in the shell command.
+    let start_directory = tmux_supports(TmuxFeature::StartDirectory);
+    let shell_command = format!("cd {} && exec $SHELL", path_str);
+    let with_env = tmux_supports(TmuxFeature::SessionEnvironment);
+
+    let mut args: Vec<String> = ["new-session", "-d", "-s", session_name]
+        .map(String::from)
+        .to_vec();
+    if with_env {
+        for (key, value) in env {
+            args.extend(["-e".to_string(), format!("{}={}", key, value)]);
+        }
+    }
+    if start_directory {
+        args.extend(["-c".to_string(), path_str.to_string()]);
+    } else {
+        args.push(shell_command.clone());
+    }
+
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    run_tmux(&args).map_err(|e| match e {
+        TmuxError::Failed(msg) => {
+            TmuxError::Failed(format!("Failed to create tmux session: {}", msg))
+        }
+        other => other,
+    })?;
+
+    // Without -e, set the session environment and restart the first pane so
+    // its shell inherits it.
+    if !with_env && !env.is_empty() {
+        for (key, value) in env {
+            // `--` so values starting with `-` are not taken as flags.
+            run_tmux(&["set-environment", "-t", session_name, "--", key, value])?;
+        }
+
+        let mut respawn = vec!["respawn-pane", "-k", "-t", session_name];
+        if !start_directory {
+            respawn.push(&shell_command);
+        }
+        run_tmux(&respawn)?;
+    }
+
+    Ok(())
+}
+
+/// The directory name, which is what sessions opened for `path` are called.
+pub fn session_name_for_path(path: &Path) -> String {
+    let name = path
         .file_name()
-        .and_then(|f| f.to_str())
-        .expect("Invalid path for session name");
+        .map(|f| f.to_string_lossy())
+        .unwrap_or_else(|| path.to_string_lossy());
+    sanitize_session_name(&name)
+}
 
-    let path_str = path.to_str().expect("Invalid path");
+/// Whether a session called exactly `session_name` exists.
+pub fn has_tmux_session(session_name: &str) -> bool {
+    run_tmux(&["has-session", "-t", &format!("={}", session_name)]).is_ok()
+}
 
-    let status = Command::new("tmux")
-        .args(&[
-            "new-session",
-            "-d",
-            "-s",
-            session_name,
-            &format!("cd {} && exec $SHELL", path_str),
-        ])
-        .status()
-        .expect("Failed to start tmux session");
+/// Creates `session_name` in `path` unless it exists and attaches to it.
+/// Usually named by [`session_name_for_path`], worktrees use `repo/branch`.
+pub fn enter_in_tmux(
+    session_name: &str,
+    path: &Path,
+    env: &[(String, String)],
+) -> Result<(), TmuxError> {
+    if !has_tmux_session(session_name) {
+        create_tmux_session(session_name, path, env)?;
+    }
+    reenter_tmux_session(session_name)
+}
 
-    if status.success() {
-        Command::new("tmux")
-            .args(&["attach-session", "-t", session_name])
-            .status()
-            .expect("Failed to attach to tmux session");
-    } else {
-        eprintln!("Failed to create tmux session");
+/// Parses a `#{window_layout}` string such as
+/// `c195,80x24,0,0[80x12,0,0,0,80x11,0,13,1]` into its pane geometries.
+pub fn parse_window_layout(layout: &str) -> Option<WindowLayout> {
+    let (_checksum, mut input) = layout.split_once(',')?;
+
+    let mut panes = Vec::new();
+    let root = parse_layout_cell(&mut input, &mut panes)?;
+
+    Some(WindowLayout {
+        width: root.width,
+        height: root.height,
+        panes,
+    })
+}
+
+/// Parses one `WxH,X,Y` cell followed by either `,<pane id>` or a
+/// `{...}` (left/right) or `[...]` (top/bottom) list of child cells.
+fn parse_layout_cell(input: &mut &str, panes: &mut Vec<PaneGeometry>) -> Option<PaneGeometry> {
+    let (width, height) = parse_layout_number_pair(input, 'x')?;
+    *input = input.strip_prefix(',')?;
+    let (x, y) = parse_layout_number_pair(input, ',')?;
+    let cell = PaneGeometry {
+        x,
+        y,
+        width,
+        height,
+    };
+
+    match input.chars().next() {
+        Some(open @ ('{' | '[')) => {
+            let close = if open == '{' { '}' } else { ']' };
+            *input = &input[1..];
+
+            loop {
+                parse_layout_cell(input, panes)?;
+                match input.chars().next()? {
+                    ',' => *input = &input[1..],
+                    c if c == close => {
+                        *input = &input[1..];
+                        return Some(cell);
+                    }
+                    _ => return None,
+                }
+            }
+        }
+        _ => {
+            // Leaf: skip the pane id if there is one. A sibling cell would
+            // start with `WxH` instead.
+            if let Some(after) = input.strip_prefix(',') {
+                let id_len = after.chars().take_while(|c| c.is_ascii_digit()).count();
+                if id_len > 0 && !after[id_len..].starts_with('x') {
+                    *input = &after[id_len..];
+                }
+            }
+
+            panes.push(cell);
+            Some(cell)
+        }
     }
 }
 
-pub fn list_tmux_sessions() -> Vec<TmuxSession> {
-    let mut sessions_child = Command::new("tmux")
-        .args(&["list-sessions", "-F", "#{session_name}:#{session_created}"])
-        .stderr(Stdio::null())
-        .stdout(Stdio::piped())
-        .spawn()
-        .expect("Failed to run tmux list-sessions");
+/// Parses `<a><separator><b>` and advances `input` past it.
+fn parse_layout_number_pair(input: &mut &str, separator: char) -> Option<(u16, u16)> {
+    let (first, rest) = input.split_once(separator)?;
+    let second_len = rest.chars().take_while(|c| c.is_ascii_digit()).count();
+
+    let pair = (first.parse().ok()?, rest[..second_len].parse().ok()?);
+    *input = &rest[second_len..];
+    Some(pair)
+}
+
+/// tmux rejects `.` and `:` in session names since they separate targets.
+pub fn sanitize_session_name(name: &str) -> String {
+    name.replace(['.', ':'], "_")
+}
+
+/// Recreates the windows, pane splits and working directories of `source`
+/// in a new detached session called `new_name`.
+pub fn clone_tmux_session(source: &str, new_name: &str) -> Result<(), TmuxError> {
+    require_tmux_feature(TmuxFeature::StartDirectory)?;
+
+    let panes_buf = run_tmux(&[
+        "list-panes",
+        "-s",
+        "-t",
+        source,
+        "-F",
+        "#{window_id}\t#{window_name}\t#{window_layout}\t#{pane_current_path}",
+    ])?;
+
+    // (window id, name, layout, pane paths) in window order
+    let mut windows: Vec<(&str, &str, &str, Vec<&str>)> = Vec::new();
+    for line in panes_buf.lines() {
+        let parts: Vec<&str> = line.splitn(4, '\t').collect();
+        if parts.len() != 4 {
+            continue;
+        }
 
-    let mut sessions_stdout = sessions_child
-        .stdout
-        .take()
-        .expect("Failed to get tmux list-sessions stdout");
+        match windows.last_mut() {
+            Some(window) if window.0 == parts[0] => window.3.push(parts[3]),
+            _ => windows.push((parts[0], parts[1], parts[2], vec![parts[3]])),
+        }
+    }
+
+    if windows.is_empty() {
+        return Err(TmuxError::Failed(format!(
+            "Session {} has no windows",
+            source
+        )));
+    }
 
-    let mut buf = String::new();
-    sessions_stdout
-        .read_to_string(&mut buf)
-        .expect("Failed to read stdout");
+    // Set once the session exists, so a failure after that point can remove
+    // the half-built copy without touching a session that was already there.
+    let mut created = false;
+    let result = (|| {
+        for (i, (_, name, layout, paths)) in windows.iter().enumerate() {
+            let window_id = if i == 0 {
+                let id = run_tmux(&[
+                    "new-session",
+                    "-d",
+                    "-P",
+                    "-F",
+                    "#{window_id}",
+                    "-s",
+                    new_name,
+                    "-n",
+                    name,
+                    "-c",
+                    paths[0],
+                ])?;
+                created = true;
+                id
+            } else {
+                run_tmux(&[
+                    "new-window",
+                    "-d",
+                    "-P",
+                    "-F",
+                    "#{window_id}",
+                    "-t",
+                    &format!("{}:", new_name),
+                    "-n",
+                    name,
+                    "-c",
+                    paths[0],
+                ])?
+            };
+            let window_id = window_id.trim();
+
+            for path in &paths[1..] {
+                run_tmux(&["split-window", "-d", "-t", window_id, "-c", path])?;
+            }
+
+            if paths.len() > 1 {
+                run_tmux(&["select-layout", "-t", window_id, layout])?;
+            }
+        }
+
+        Ok(())
+    })();
+
+    if result.is_err() && created {
+        let _ = run_tmux(&["kill-session", "-t", &format!("={}", new_name)]);
+    }
+
+    result
+}
+
+pub fn list_tmux_sessions() -> Result<Vec<TmuxSession>, TmuxError> {
+    check_tmux_version()?;
+
+    let buf = run_tmux(&["list-sessions", "-F", "#{session_name}:#{session_created}"])?;
 
     let mut sessions = Vec::new();
 
@@ -101,39 +521,28 @@ pub fn list_tmux_sessions() -> Vec<TmuxSession> {
         let session_name = parts[0].to_string();
         let date_created = parse_tmux_timestamp(parts[1]);
 
-        let mut windows_child = Command::new("tmux")
-            .args(&[
-                "list-windows",
-                "-t",
-                &session_name,
-                "-F",
-                "#{window_index}:#{window_name}:#{window_active}",
-            ])
-            .stdout(Stdio::piped())
-            .spawn()
-            .expect("Failed to run tmux list-windows");
-
-        let mut windows_stdout = windows_child
-            .stdout
-            .take()
-            .expect("Failed to get tmux list-windows stdout");
-
-        let mut windows_buf = String::new();
-        windows_stdout
-            .read_to_string(&mut windows_buf)
-            .expect("Failed to read windows stdout");
+        let windows_buf = run_tmux(&[
+            "list-windows",
+            "-t",
+            &session_name,
+            "-F",
+            "#{window_index}:#{window_active}:#{window_layout}:#{window_name}",
+        ])
+        .unwrap_or_default();
 
         let mut windows = Vec::new();
         for win_line in windows_buf.lines() {
-            let win_parts: Vec<&str> = win_line.splitn(3, ':').collect();
-            if win_parts.len() != 3 {
+            // The name goes last since it is the only field that may contain ':'.
+            let win_parts: Vec<&str> = win_line.splitn(4, ':').collect();
+            if win_parts.len() != 4 {
                 continue;
             }
 
             windows.push(TmuxWindow {
                 index: win_parts[0].to_string(),
-                name: win_parts[1].to_string(),
-                active: win_parts[2] == "1",
+                name: win_parts[3].to_string(),
+                active: win_parts[1] == "1",
+                layout: parse_window_layout(win_parts[2]),
             });
         }
 
@@ -144,5 +553,104 @@ pub fn list_tmux_sessions() -> Vec<TmuxSession> {
         });
     }
 
-    sessions
+    Ok(sessions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pane(x: u16, y: u16, width: u16, height: u16) -> PaneGeometry {
+        PaneGeometry {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn parses_single_pane_layout() {
+        let layout = parse_window_layout("b25d,80x24,0,0,0").unwrap();
+
+        assert_eq!((layout.width, layout.height), (80, 24));
+        assert_eq!(layout.panes, [pane(0, 0, 80, 24)]);
+    }
+
+    #[test]
+    fn parses_nested_splits() {
+        let layout = parse_window_layout(
+            "c195,80x24,0,0{40x24,0,0,1,39x24,41,0[39x12,41,0,2,39x11,41,13,3]}",
+        )
+        .unwrap();
+
+        assert_eq!(
+            layout.panes,
+            [
+                pane(0, 0, 40, 24),
+                pane(41, 0, 39, 12),
+                pane(41, 13, 39, 11)
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_layout_without_pane_ids() {
+        // tmux before 2.0 did not include pane ids in layouts.
+        let layout = parse_window_layout("bb62,159x48,0,0{79x48,0,0,79x48,80,0}").unwrap();
+
+        assert_eq!(layout.panes, [pane(0, 0, 79, 48), pane(80, 0, 79, 48)]);
+    }
+
+    #[test]
+    fn rejects_malformed_layouts() {
+        assert_eq!(parse_window_layout(""), None);
+        assert_eq!(parse_window_layout("c195,80x24"), None);
+        assert_eq!(parse_window_layout("c195,80x24,0,0{40x24,0,0,1"), None);
+    }
+
+    #[test]
+    fn classifies_tmux_errors() {
+        assert_eq!(
+            classify_tmux_stderr("no server running on /tmp/tmux-1000/default\n"),
+            TmuxError::NoServer
+        );
+        assert_eq!(
+            classify_tmux_stderr(
+                "error connecting to /tmp/tmux-1000/default (No such file or directory)"
+            ),
+            TmuxError::NoServer
+        );
+        assert_eq!(
+            classify_tmux_stderr("error connecting to /tmp/tmux-0/default (Permission denied)"),
+            TmuxError::PermissionDenied(
+                "error connecting to /tmp/tmux-0/default (Permission denied)".to_string()
+            )
+        );
+        assert_eq!(
+            classify_tmux_stderr("can't find session: foo\n"),
+            TmuxError::Failed("can't find session: foo".to_string())
+        );
+    }
+
+    #[test]
+    fn parses_tmux_versions() {
+        let cases = [
+            ("tmux 3.3a\n", Some((3, 3))),
+            ("tmux 1.8", Some((1, 8))),
+            ("tmux next-3.5", Some((3, 5))),
+            ("tmux openbsd-7.4", Some((7, 4))),
+            ("tmux master", None),
+            ("screen 4.9", None),
+        ];
+
+        for (output, expected) in cases {
+            assert_eq!(
+                parse_tmux_version(output),
+                expected.map(|(major, minor)| TmuxVersion { major, minor }),
+                "{}",
+                output
+            );
+        }
+    }
 }
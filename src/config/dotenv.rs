@@ -0,0 +1,110 @@
+use std::{fs, io, path::Path};
+
+/// Reads a `.env` file into `KEY=VALUE` pairs. Supports `#` comments, an
+/// optional `export ` prefix and single or double quoted values.
+pub fn load_dotenv(path: &Path) -> io::Result<Vec<(String, String)>> {
+    Ok(parse_dotenv(&fs::read_to_string(path)?))
+}
+
+pub fn parse_dotenv(contents: &str) -> Vec<(String, String)> {
+    let mut vars = Vec::new();
+
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+
+        let key = key.trim();
+        if key.is_empty() {
+            continue;
+        }
+
+        vars.push((key.to_string(), unquote(value.trim()).to_string()));
+    }
+
+    vars
+}
+
+fn unquote(value: &str) -> &str {
+    for quote in ['"', '\''] {
+        if value.len() >= 2 && value.starts_with(quote) && value.ends_with(quote) {
+            return &value[1..value.len() - 1];
+        }
+    }
+
+    // Unquoted values may carry a trailing comment.
+    match value.find(" #") {
+        Some(pos) => value[..pos].trim_end(),
+        None => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pair(key: &str, value: &str) -> (String, String) {
+        (key.to_string(), value.to_string())
+    }
+
+    #[test]
+    fn skips_comments_blank_and_malformed_lines() {
+        let contents = "\
+# database settings
+
+DB_HOST=localhost
+not a variable
+=no_key
+  PORT = 5432  
+";
+
+        assert_eq!(
+            parse_dotenv(contents),
+            [pair("DB_HOST", "localhost"), pair("PORT", "5432")]
+        );
+    }
+
+    #[test]
+    fn strips_export_prefix() {
+        assert_eq!(
+            parse_dotenv("export EDITOR=vim\nexported=yes"),
+            [pair("EDITOR", "vim"), pair("exported", "yes")]
+        );
+    }
+
+    #[test]
+    fn unquotes_values() {
+        let contents = "\
+DOUBLE=\"hello world\"
+SINGLE='it # is not a comment'
+EMPTY=\"\"
+LONE=\"
+EQUALS=a=b
+";
+
+        assert_eq!(
+            parse_dotenv(contents),
+            [
+                pair("DOUBLE", "hello world"),
+                pair("SINGLE", "it # is not a comment"),
+                pair("EMPTY", ""),
+                pair("LONE", "\""),
+                pair("EQUALS", "a=b"),
+            ]
+        );
+    }
+
+    #[test]
+    fn drops_trailing_comments_of_unquoted_values() {
+        assert_eq!(
+            parse_dotenv("URL=http://host/#anchor # the server\nCOLOR=#fff"),
+            [pair("URL", "http://host/#anchor"), pair("COLOR", "#fff")]
+        );
+    }
+}
@@ -0,0 +1,307 @@
+use std::{
+    env, fs, io,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::config::settings::{data_dir, write_atomically};
+
+/// Once the ranks add up to more than this, every rank is scaled down so
+/// old entries fade out.
+const MAX_TOTAL_RANK: f64 = 10_000.0;
+const AGING_FACTOR: f64 = 0.9;
+
+const HOUR: u64 = 60 * 60;
+const DAY: u64 = 24 * HOUR;
+const WEEK: u64 = 7 * DAY;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+    Directory,
+    Session,
+}
+
+impl EntryKind {
+    fn tag(self) -> &'static str {
+        match self {
+            EntryKind::Directory => "d",
+            EntryKind::Session => "s",
+        }
+    }
+
+    fn from_tag(tag: &str) -> Option<Self> {
+        match tag {
+            "d" => Some(EntryKind::Directory),
+            "s" => Some(EntryKind::Session),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrecencyEntry {
+    pub kind: EntryKind,
+    /// Absolute path for directories, session name for sessions.
+    pub value: String,
+    pub rank: f64,
+    /// Seconds since the Unix epoch.
+    pub last_accessed: u64,
+}
+
+impl FrecencyEntry {
+    /// zoxide-style score: the rank weighted by how recently it was used.
+    pub fn score(&self, now: u64) -> f64 {
+        let age = now.saturating_sub(self.last_accessed);
+        let weight = if age < HOUR {
+            4.0
+        } else if age < DAY {
+            2.0
+        } else if age < WEEK {
+            0.5
+        } else {
+            0.25
+        };
+
+        self.rank * weight
+    }
+}
+
+/// Directories and sessions Pinta opened, stored one per line as
+/// `<kind>\t<rank>\t<last accessed>\t<value>`.
+#[derive(Default, Debug)]
+pub struct FrecencyStore {
+    pub entries: Vec<FrecencyEntry>,
+    path: Option<PathBuf>,
+}
+
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+impl FrecencyStore {
+    /// Loads the store from the data directory, empty if it does not exist.
+    pub fn load() -> io::Result<Self> {
+        let Some(path) = data_dir("pinta").map(|dir| dir.join("frecency")) else {
+            return Ok(Self::default());
+        };
+
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e),
+        };
+
+        let entries = contents
+            .lines()
+            .filter_map(|line| {
+                let mut parts = line.splitn(4, '\t');
+                Some(FrecencyEntry {
+                    kind: EntryKind::from_tag(parts.next()?)?,
+                    rank: parts.next()?.parse().ok()?,
+                    last_accessed: parts.next()?.parse().ok()?,
+                    value: parts.next()?.to_string(),
+                })
+            })
+            .collect();
+
+        Ok(Self {
+            entries,
+            path: Some(path),
+        })
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        let contents: String = self
+            .entries
+            .iter()
+            .map(|e| {
+                format!(
+                    "{}\t{}\t{}\t{}\n",
+                    e.kind.tag(),
+                    e.rank,
+                    e.last_accessed,
+                    e.value
+                )
+            })
+            .collect();
+
+        write_atomically(path, contents)
+    }
+
+    pub fn add(&mut self, kind: EntryKind, value: &str) {
+        let now = now();
+
+        match self
+            .entries
+            .iter_mut()
+            .find(|e| e.kind == kind && e.value == value)
+        {
+            Some(entry) => {
+                entry.rank += 1.0;
+                entry.last_accessed = now;
+            }
+            None => self.entries.push(FrecencyEntry {
+                kind,
+                value: value.to_string(),
+                rank: 1.0,
+                last_accessed: now,
+            }),
+        }
+
+        self.age();
+    }
+
+    fn age(&mut self) {
+        let total: f64 = self.entries.iter().map(|e| e.rank).sum();
+        if total <= MAX_TOTAL_RANK {
+            return;
+        }
+
+        for entry in &mut self.entries {
+            entry.rank *= AGING_FACTOR;
+        }
+        self.entries.retain(|e| e.rank >= 1.0);
+    }
+
+    /// Entries with the highest score first.
+    pub fn sorted(&self) -> Vec<FrecencyEntry> {
+        let now = now();
+        let mut entries = self.entries.clone();
+        entries.sort_by(|a, b| b.score(now).total_cmp(&a.score(now)));
+        entries
+    }
+
+    /// Merges zoxide's `db.zo` into the store, keeping the higher rank and
+    /// the later access time for directories known to both. Returns the
+    /// number of directories read.
+    pub fn import_zoxide(&mut self) -> io::Result<usize> {
+        let path = zoxide_database_path().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, "Could not locate zoxide's db.zo")
+        })?;
+        let dirs = parse_zoxide_database(&fs::read(&path)?).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Unsupported zoxide database: {}", path.display()),
+            )
+        })?;
+
+        let count = dirs.len();
+        for (value, rank, last_accessed) in dirs {
+            match self
+                .entries
+                .iter_mut()
+                .find(|e| e.kind == EntryKind::Directory && e.value == value)
+            {
+                Some(entry) => {
+                    entry.rank = entry.rank.max(rank);
+                    entry.last_accessed = entry.last_accessed.max(last_accessed);
+                }
+                None => self.entries.push(FrecencyEntry {
+                    kind: EntryKind::Directory,
+                    value,
+                    rank,
+                    last_accessed,
+                }),
+            }
+        }
+
+        self.age();
+        Ok(count)
+    }
+}
+
+fn zoxide_database_path() -> Option<PathBuf> {
+    env::var_os("_ZO_DATA_DIR")
+        .map(PathBuf::from)
+        .or_else(|| data_dir("zoxide"))
+        .map(|dir| dir.join("db.zo"))
+        .filter(|path| path.exists())
+}
+
+/// Decodes zoxide's version 3 database: a bincode-encoded `u32` version
+/// followed by a `Vec` of `(path, rank: f64, last_accessed: u64)`.
+fn parse_zoxide_database(bytes: &[u8]) -> Option<Vec<(String, f64, u64)>> {
+    fn take<'a>(bytes: &mut &'a [u8], n: usize) -> Option<&'a [u8]> {
+        let (head, tail) = bytes.split_at_checked(n)?;
+        *bytes = tail;
+        Some(head)
+    }
+    fn u64_le(bytes: &mut &[u8]) -> Option<u64> {
+        Some(u64::from_le_bytes(take(bytes, 8)?.try_into().ok()?))
+    }
+
+    let mut bytes = bytes;
+    let version = u32::from_le_bytes(take(&mut bytes, 4)?.try_into().ok()?);
+    if version != 3 {
+        return None;
+    }
+
+    let len = u64_le(&mut bytes)?;
+    let mut dirs = Vec::new();
+    for _ in 0..len {
+        let path_len = u64_le(&mut bytes)? as usize;
+        let path = String::from_utf8(take(&mut bytes, path_len)?.to_vec()).ok()?;
+        let rank = f64::from_le_bytes(take(&mut bytes, 8)?.try_into().ok()?);
+        let last_accessed = u64_le(&mut bytes)?;
+        dirs.push((path, rank, last_accessed));
+    }
+
+    Some(dirs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A version 3 database holding `/home/me/code` (rank 2.5, accessed at
+    /// 1700000000) and `/tmp` (rank 1.0, accessed at 1600000000).
+    const ZOXIDE_V3: &[u8] = &[
+        3, 0, 0, 0, // version
+        2, 0, 0, 0, 0, 0, 0, 0, // entries
+        13, 0, 0, 0, 0, 0, 0, 0, b'/', b'h', b'o', b'm', b'e', b'/', b'm', b'e', b'/', b'c', b'o',
+        b'd', b'e', // path
+        0, 0, 0, 0, 0, 0, 4, 64, // rank
+        0, 241, 83, 101, 0, 0, 0, 0, // last accessed
+        4, 0, 0, 0, 0, 0, 0, 0, b'/', b't', b'm', b'p', // path
+        0, 0, 0, 0, 0, 0, 240, 63, // rank
+        0, 16, 94, 95, 0, 0, 0, 0, // last accessed
+    ];
+
+    #[test]
+    fn decodes_zoxide_v3_database() {
+        assert_eq!(
+            parse_zoxide_database(ZOXIDE_V3),
+            Some(vec![
+                ("/home/me/code".to_string(), 2.5, 1_700_000_000),
+                ("/tmp".to_string(), 1.0, 1_600_000_000),
+            ])
+        );
+    }
+
+    #[test]
+    fn rejects_truncated_zoxide_database() {
+        for len in [0, 3, 12, 30, ZOXIDE_V3.len() - 1] {
+            assert_eq!(
+                parse_zoxide_database(&ZOXIDE_V3[..len]),
+                None,
+                "length {}",
+                len
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_other_zoxide_versions() {
+        let mut bytes = ZOXIDE_V3.to_vec();
+        bytes[0] = 4;
+
+        assert_eq!(parse_zoxide_database(&bytes), None);
+    }
+}
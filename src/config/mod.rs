@@ -0,0 +1,4 @@
+pub mod dotenv;
+pub mod frecency;
+pub mod settings;
+pub mod state;
@@ -0,0 +1,198 @@
+use std::{
+    collections::BTreeMap,
+    env, fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+use crate::{config::dotenv::load_dotenv, paths::directories::expand_tilde};
+
+/// Per-project settings file looked up in the directory being opened.
+pub const PROJECT_CONFIG_FILE: &str = ".pinta.toml";
+
+/// `~/.config/pinta/config.toml`
+#[derive(Default, Debug, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Variables set in every session Pinta creates.
+    pub env: BTreeMap<String, String>,
+    /// Directories (and everything below them) whose `.env` and `.pinta.toml`
+    /// may set session variables. Other directories only get the config's env.
+    pub trusted_directories: Vec<PathBuf>,
+    /// Settings keyed by project path, `~` is expanded.
+    pub projects: BTreeMap<PathBuf, ProjectConfig>,
+    /// Directories scanned for projects, e.g. `{ path = "~/code", depth = 3 }`.
+    pub project_roots: Vec<ProjectRoot>,
+    /// Overrides the default project markers (`.git`, `Cargo.toml`, ...).
+    pub project_markers: Vec<String>,
+    pub fzf: FzfConfig,
+    /// Start with `.gitignore`, `.ignore` and `exclude` applied (toggle: I).
+    pub respect_ignore: bool,
+    /// Globs hidden while ignore filtering is on, e.g. `["node_modules", "target"]`.
+    pub exclude: Vec<String>,
+}
+
+/// The `[fzf]` table, used when picking through an external fzf.
+#[derive(Default, Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct FzfConfig {
+    /// Binary to run, `fzf` when unset.
+    pub command: Option<String>,
+    /// Extra arguments, e.g. `["--reverse", "--height=40%"]`.
+    pub options: Vec<String>,
+    /// `--preview` for directory entries and projects, `{}` is the path.
+    pub preview: Option<String>,
+    /// `--preview` for tmux sessions, `{}` is the session name.
+    pub session_preview: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProjectRoot {
+    pub path: PathBuf,
+    #[serde(default = "default_root_depth")]
+    pub depth: usize,
+}
+
+fn default_root_depth() -> usize {
+    2
+}
+
+/// A `[projects."<path>"]` table in the config, or a `.pinta.toml`.
+#[derive(Default, Debug, Deserialize)]
+#[serde(default)]
+pub struct ProjectConfig {
+    pub env: BTreeMap<String, String>,
+    /// Dotenv file relative to the project, `.env` when unset.
+    pub env_file: Option<PathBuf>,
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(PathBuf, io::Error),
+    Parse(PathBuf, toml::de::Error),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(path, e) => write!(f, "Could not read {}: {}", path.display(), e),
+            ConfigError::Parse(path, e) => write!(f, "Invalid {}: {}", path.display(), e),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+pub fn config_dir() -> Option<PathBuf> {
+    env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .map(|dir| dir.join("pinta"))
+}
+
+/// `$XDG_DATA_HOME/<app>` or `~/.local/share/<app>`.
+pub fn data_dir(app: &str) -> Option<PathBuf> {
+    env::var_os("XDG_DATA_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+        .map(|dir| dir.join(app))
+}
+
+/// Replaces `path` with `contents`, creating its directory if needed. The
+/// bytes go to a temporary file that is renamed over `path`, so a crash
+/// halfway through cannot leave a truncated file behind.
+pub fn write_atomically(path: &Path, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, contents)?;
+    fs::rename(tmp, path)
+}
+
+fn read_toml<T: for<'de> Deserialize<'de>>(path: &Path) -> Result<Option<T>, ConfigError> {
+    match fs::read_to_string(path) {
+        Ok(contents) => toml::from_str(&contents)
+            .map(Some)
+            .map_err(|e| ConfigError::Parse(path.to_path_buf(), e)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(ConfigError::Io(path.to_path_buf(), e)),
+    }
+}
+
+/// Loads the user config, falling back to defaults when there is none.
+pub fn load_config() -> Result<Config, ConfigError> {
+    match config_dir() {
+        Some(dir) => Ok(read_toml(&dir.join("config.toml"))?.unwrap_or_default()),
+        None => Ok(Config::default()),
+    }
+}
+
+impl Config {
+    /// Whether `dir` is inside one of the `trusted_directories`.
+    pub fn is_trusted(&self, dir: &Path) -> bool {
+        self.trusted_directories
+            .iter()
+            .any(|trusted| dir.starts_with(expand_tilde(trusted)))
+    }
+}
+
+/// Collects the environment for a session in `dir`. Later sources win:
+/// global `[env]`, the project's entry in the config, its dotenv file and
+/// finally the `[env]` table of its `.pinta.toml`. Files in `dir` are only
+/// read when it is trusted; a configured `env_file` is always read.
+///
+/// Files that cannot be read are skipped and returned as warnings, so a
+/// broken `.env` never keeps a session from being created.
+pub fn project_environment(
+    config: &Config,
+    dir: &Path,
+) -> (Vec<(String, String)>, Vec<ConfigError>) {
+    let mut warnings = Vec::new();
+    let trusted = config.is_trusted(dir);
+
+    let local: ProjectConfig = if trusted {
+        read_toml(&dir.join(PROJECT_CONFIG_FILE))
+            .unwrap_or_else(|e| {
+                warnings.push(e);
+                None
+            })
+            .unwrap_or_default()
+    } else {
+        ProjectConfig::default()
+    };
+    let configured = config
+        .projects
+        .iter()
+        .find(|(path, _)| expand_tilde(path) == dir)
+        .map(|(_, project)| project);
+
+    let mut vars = config.env.clone();
+
+    if let Some(project) = configured {
+        vars.extend(project.env.clone());
+    }
+
+    let env_file = local
+        .env_file
+        .as_ref()
+        .or(configured.and_then(|p| p.env_file.as_ref()))
+        .map(|file| dir.join(file))
+        .or_else(|| trusted.then(|| dir.join(".env")));
+
+    if let Some(env_file) = env_file {
+        match load_dotenv(&env_file) {
+            Ok(dotenv) => vars.extend(dotenv),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => warnings.push(ConfigError::Io(env_file, e)),
+        }
+    }
+
+    vars.extend(local.env);
+
+    (vars.into_iter().collect(), warnings)
+}
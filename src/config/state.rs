@@ -0,0 +1,78 @@
+use std::{collections::BTreeMap, fs, io, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::settings::{data_dir, write_atomically},
+    paths::directories::SortOrder,
+};
+
+/// Choices made in the UI that should survive a restart, kept apart from
+/// the hand-written config in `~/.local/share/pinta/state.toml`.
+#[derive(Default, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct State {
+    pub sort: SortOrder,
+    /// Size, modification time, permissions and owner next to each entry.
+    pub show_metadata: bool,
+    /// Preview pane next to the directories, projects and recent lists.
+    pub show_preview: bool,
+    /// Directory marks set with `m<letter>`, keyed by the letter.
+    pub marks: BTreeMap<String, PathBuf>,
+}
+
+fn state_path() -> Option<PathBuf> {
+    data_dir("pinta").map(|dir| dir.join("state.toml"))
+}
+
+impl State {
+    /// Loads the saved state, with a warning when it had to fall back to
+    /// the defaults; losing UI state is not worth refusing to start over. A
+    /// file that does not parse is moved to `state.toml.bak` first, so the
+    /// next save cannot lose the marks in it.
+    pub fn load() -> (Self, Option<String>) {
+        let Some(path) = state_path() else {
+            return (Self::default(), None);
+        };
+
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return (Self::default(), None),
+            Err(e) => {
+                let warning = format!("Could not read {}: {}", path.display(), e);
+                return (Self::default(), Some(warning));
+            }
+        };
+
+        match toml::from_str(&contents) {
+            Ok(state) => (state, None),
+            Err(e) => {
+                let backup = path.with_extension("toml.bak");
+                let warning = match fs::rename(&path, &backup) {
+                    Ok(()) => format!(
+                        "Invalid {}: {}, moved it to {}",
+                        path.display(),
+                        e.message(),
+                        backup.display()
+                    ),
+                    Err(rename) => format!(
+                        "Invalid {}: {}, and could not back it up: {}",
+                        path.display(),
+                        e.message(),
+                        rename
+                    ),
+                };
+                (Self::default(), Some(warning))
+            }
+        }
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let Some(path) = state_path() else {
+            return Ok(());
+        };
+
+        let contents = toml::to_string(self).map_err(io::Error::other)?;
+        write_atomically(&path, contents)
+    }
+}
@@ -1,14 +1,37 @@
 use app::app::App;
+use clap::Parser;
+use cli::{Args, Command, init_script};
 use color_eyre::Result;
+use config::settings::load_config;
 mod app;
+mod cli;
 mod commands;
+mod config;
 mod paths;
+mod terminal;
 mod ui;
 
 fn main() -> Result<()> {
-    color_eyre::install()?;
-    let mut terminal = ratatui::init();
-    let app_result = App::default().run(&mut terminal);
-    ratatui::restore();
-    app_result
+    let args = Args::parse();
+    if let Err(e) = args.check_subcommand() {
+        e.exit();
+    }
+    if let Some(Command::Init { shell }) = args.command {
+        print!("{}", init_script(shell));
+        return Ok(());
+    }
+
+    terminal::install_hooks()?;
+    let config = load_config()?;
+    let mut terminal = terminal::init(args.pick)?;
+    let app_result = App::new(config, &args).run(&mut terminal);
+    terminal::restore();
+
+    match app_result? {
+        Some(picked) => println!("{}", picked),
+        // Like fzf, so scripts can tell a cancelled pick from an empty one.
+        None if args.pick => std::process::exit(130),
+        None => {}
+    }
+    Ok(())
 }
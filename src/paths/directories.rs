@@ -1,21 +1,234 @@
 use std::{
-    env::current_dir,
-    fs,
+    cmp::Ordering,
+    collections::HashSet,
+    env::{self, current_dir},
+    fmt,
+    fs::{self, Metadata},
+    io,
     path::{Path, PathBuf},
 };
 
-pub fn get_current_directory_name() -> PathBuf {
-    return current_dir()
-        .expect("Could not get current directory")
-        .to_path_buf();
+use ignore::{WalkBuilder, overrides::OverrideBuilder};
+use serde::{Deserialize, Serialize};
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortMode {
+    /// Natural order, so `v2` comes before `v10`.
+    #[default]
+    Name,
+    Modified,
+    Size,
+    Extension,
+}
+
+impl SortMode {
+    pub fn next(self) -> Self {
+        match self {
+            SortMode::Name => SortMode::Modified,
+            SortMode::Modified => SortMode::Size,
+            SortMode::Size => SortMode::Extension,
+            SortMode::Extension => SortMode::Name,
+        }
+    }
+}
+
+impl fmt::Display for SortMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SortMode::Name => write!(f, "name"),
+            SortMode::Modified => write!(f, "modified"),
+            SortMode::Size => write!(f, "size"),
+            SortMode::Extension => write!(f, "extension"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SortOrder {
+    pub mode: SortMode,
+    pub reverse: bool,
+    /// Directories stay on top regardless of `reverse`.
+    pub directories_first: bool,
+}
+
+impl Default for SortOrder {
+    fn default() -> Self {
+        Self {
+            mode: SortMode::Name,
+            reverse: false,
+            directories_first: true,
+        }
+    }
+}
+
+impl fmt::Display for SortOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {}{}",
+            self.mode,
+            if self.reverse { "↓" } else { "↑" },
+            if self.directories_first {
+                ", dirs first"
+            } else {
+                ""
+            }
+        )
+    }
+}
+
+/// Compares names chunk by chunk, numbers by value and text ignoring case.
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let mut a_chars = a.chars().peekable();
+    let mut b_chars = b.chars().peekable();
+
+    loop {
+        match (a_chars.peek().copied(), b_chars.peek().copied()) {
+            (None, None) => return a.cmp(b),
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let take_number = |chars: &mut std::iter::Peekable<std::str::Chars>| {
+                    let mut digits = String::new();
+                    while let Some(c) = chars.next_if(|c| c.is_ascii_digit()) {
+                        digits.push(c);
+                    }
+                    digits.trim_start_matches('0').to_string()
+                };
+                let x_num = take_number(&mut a_chars);
+                let y_num = take_number(&mut b_chars);
+
+                let ordering = x_num.len().cmp(&y_num.len()).then(x_num.cmp(&y_num));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            (Some(x), Some(y)) => {
+                let ordering = x.to_lowercase().cmp(y.to_lowercase());
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+                a_chars.next();
+                b_chars.next();
+            }
+        }
+    }
+}
+
+/// Sorts `entries` in place. Entries whose metadata cannot be read sort as
+/// if they were empty and infinitely old.
+pub fn sort_entries(entries: &mut Vec<PathBuf>, order: &SortOrder) {
+    let name = |path: &Path| {
+        path.file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned()
+    };
+    let extension = |path: &Path| {
+        path.extension()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_lowercase()
+    };
+
+    let mut keyed: Vec<(PathBuf, Option<Metadata>)> = entries
+        .drain(..)
+        .map(|path| {
+            let metadata = fs::metadata(&path).ok();
+            (path, metadata)
+        })
+        .collect();
+
+    keyed.sort_by(|(a, a_meta), (b, b_meta)| {
+        let by_mode = match order.mode {
+            SortMode::Name => Ordering::Equal,
+            SortMode::Modified => {
+                let modified = |m: &Option<Metadata>| m.as_ref().and_then(|m| m.modified().ok());
+                modified(a_meta).cmp(&modified(b_meta))
+            }
+            SortMode::Size => {
+                let size = |m: &Option<Metadata>| m.as_ref().map(Metadata::len);
+                size(a_meta).cmp(&size(b_meta))
+            }
+            SortMode::Extension => extension(a).cmp(&extension(b)),
+        }
+        .then_with(|| natural_cmp(&name(a), &name(b)));
+
+        let by_mode = if order.reverse {
+            by_mode.reverse()
+        } else {
+            by_mode
+        };
+
+        if order.directories_first {
+            let is_dir = |m: &Option<Metadata>| m.as_ref().is_some_and(Metadata::is_dir);
+            is_dir(b_meta).cmp(&is_dir(a_meta)).then(by_mode)
+        } else {
+            by_mode
+        }
+    });
+
+    entries.extend(keyed.into_iter().map(|(path, _)| path));
+}
+
+/// Which entries directory listings and subtree walks leave out.
+#[derive(Default, Debug, Clone)]
+pub struct ListingFilter {
+    pub show_hidden: bool,
+    /// Honour `.gitignore`, `.ignore` and `exclude`.
+    pub respect_ignore: bool,
+    /// Globs such as `node_modules` or `target`, matched like `.gitignore`.
+    pub exclude: Vec<String>,
+}
+
+/// A walker over `dir` that applies `filter`.
+pub fn walk_builder(dir: &Path, filter: &ListingFilter) -> WalkBuilder {
+    let mut builder = WalkBuilder::new(dir);
+    builder
+        .hidden(!filter.show_hidden)
+        .parents(filter.respect_ignore)
+        .ignore(filter.respect_ignore)
+        .git_ignore(filter.respect_ignore)
+        .git_global(filter.respect_ignore)
+        .git_exclude(filter.respect_ignore);
+
+    if filter.respect_ignore && !filter.exclude.is_empty() {
+        let mut overrides = OverrideBuilder::new(dir);
+        for glob in &filter.exclude {
+            // Invalid globs are skipped rather than hiding everything.
+            let _ = overrides.add(&format!("!{}", glob));
+        }
+        if let Ok(overrides) = overrides.build() {
+            builder.overrides(overrides);
+        }
+    }
+
+    builder
+}
+
+/// Fails when the working directory was deleted or is not accessible.
+pub fn get_current_directory_name() -> io::Result<PathBuf> {
+    current_dir()
 }
 
-pub fn get_current_directory_contents(dir: &Path, show_hidden: bool) -> Vec<PathBuf> {
-    fs::read_dir(dir)
-        .unwrap_or_else(|_| panic!("Could not read current directory: {}", dir.display()))
+pub fn get_current_directory_contents(
+    dir: &Path,
+    filter: &ListingFilter,
+) -> io::Result<Vec<PathBuf>> {
+    let kept: Option<HashSet<PathBuf>> = filter.respect_ignore.then(|| {
+        walk_builder(dir, filter)
+            .max_depth(Some(1))
+            .build()
+            .filter_map(|entry| entry.ok().map(|e| e.into_path()))
+            .collect()
+    });
+
+    Ok(fs::read_dir(dir)?
         .filter_map(|entry| entry.ok().map(|e| e.path()))
         .filter(|path| {
-            if show_hidden {
+            if filter.show_hidden {
                 true
             } else {
                 path.file_name()
@@ -24,5 +237,197 @@ pub fn get_current_directory_contents(dir: &Path, show_hidden: bool) -> Vec<Path
                     .unwrap_or(true)
             }
         })
-        .collect()
+        .filter(|path| kept.as_ref().is_none_or(|kept| kept.contains(path)))
+        .collect())
+}
+
+/// Completes the last component of `input` against the directories in
+/// its parent, which is relative to `base` unless absolute or `~/...`.
+/// Returns the input extended by the longest common prefix of the matches,
+/// and the matching names when there is more than one.
+pub fn complete_directory(input: &str, base: &Path, show_hidden: bool) -> (String, Vec<String>) {
+    let (parent, prefix) = match input.rfind('/') {
+        Some(i) => input.split_at(i + 1),
+        None if input == "~" => return (String::from("~/"), Vec::new()),
+        None => ("", input),
+    };
+
+    let parent_dir = base.join(expand_tilde(Path::new(parent)));
+    let Ok(entries) = fs::read_dir(&parent_dir) else {
+        return (input.to_string(), Vec::new());
+    };
+
+    let mut matches: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .filter(|name| name.starts_with(prefix))
+        .filter(|name| show_hidden || prefix.starts_with('.') || !name.starts_with('.'))
+        .collect();
+    matches.sort_by(|a, b| natural_cmp(a, b));
+
+    match matches.as_slice() {
+        [] => (input.to_string(), Vec::new()),
+        [only] => (format!("{}{}/", parent, only), Vec::new()),
+        [first, rest @ ..] => {
+            let common = rest.iter().fold(first.as_str(), |common, name| {
+                let len = common
+                    .char_indices()
+                    .zip(name.chars())
+                    .find(|((_, a), b)| a != b)
+                    .map_or(common.len().min(name.len()), |((i, _), _)| i);
+                &common[..len]
+            });
+            (format!("{}{}", parent, common), matches.clone())
+        }
+    }
+}
+
+/// Expands a leading `~` to `$HOME`.
+pub fn expand_tilde(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), env::var_os("HOME")) {
+        (Ok(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh directory under the system temp dir with these subdirectories
+    /// and a `notes.txt` file.
+    fn fixture(name: &str, dirs: &[&str]) -> PathBuf {
+        let root = env::temp_dir().join(format!("pinta-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        for dir in dirs {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        fs::write(root.join("notes.txt"), "").unwrap();
+        root
+    }
+
+    #[test]
+    fn completes_unique_match_with_slash() {
+        let root = fixture("unique", &["projects/pinta", "public"]);
+
+        assert_eq!(
+            complete_directory("pr", &root, false),
+            ("projects/".to_string(), Vec::new())
+        );
+        assert_eq!(
+            complete_directory("projects/p", &root, false),
+            ("projects/pinta/".to_string(), Vec::new())
+        );
+        assert_eq!(
+            complete_directory("no", &root, false),
+            ("no".to_string(), Vec::new())
+        );
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn completes_common_prefix_of_several_matches() {
+        let root = fixture("common", &["pictures", "pics", "public", ".pi"]);
+
+        assert_eq!(
+            complete_directory("pi", &root, false),
+            (
+                "pic".to_string(),
+                vec!["pics".to_string(), "pictures".to_string()]
+            )
+        );
+        assert_eq!(
+            complete_directory(".p", &root, false),
+            (".pi/".to_string(), Vec::new())
+        );
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn common_prefix_keeps_multibyte_chars_whole() {
+        let root = fixture("multibyte", &["größe", "größer", "grün", "ärger", "ärmel"]);
+
+        assert_eq!(
+            complete_directory("grö", &root, false).0,
+            "größe".to_string()
+        );
+        assert_eq!(complete_directory("g", &root, false).0, "gr".to_string());
+        assert_eq!(complete_directory("ä", &root, false).0, "är".to_string());
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn completes_home_directory() {
+        assert_eq!(
+            complete_directory("~", Path::new("/"), false),
+            ("~/".to_string(), Vec::new())
+        );
+
+        let Some(home) = env::var_os("HOME") else {
+            return;
+        };
+        let (completed, candidates) = complete_directory("~/", Path::new("/"), false);
+        assert!(completed.starts_with("~/"), "{}", completed);
+        for name in candidates {
+            assert!(Path::new(&home).join(name).is_dir());
+        }
+    }
+
+    #[test]
+    fn natural_cmp_orders_numbers_by_value() {
+        assert_eq!(natural_cmp("v2", "v10"), Ordering::Less);
+        assert_eq!(natural_cmp("v10", "v9"), Ordering::Greater);
+        assert_eq!(natural_cmp("v1.2.10", "v1.2.9"), Ordering::Greater);
+        assert_eq!(natural_cmp("10", "9a"), Ordering::Greater);
+    }
+
+    #[test]
+    fn natural_cmp_ignores_leading_zeros_but_stays_total() {
+        assert_eq!(natural_cmp("v010", "v9"), Ordering::Greater);
+        assert_eq!(natural_cmp("v007", "v08"), Ordering::Less);
+        // Equal values fall back to the raw names, so no two differ by zeros only.
+        assert_eq!(natural_cmp("v007", "v7"), Ordering::Less);
+        assert_eq!(natural_cmp("v7", "v007"), Ordering::Greater);
+    }
+
+    #[test]
+    fn natural_cmp_ignores_case_but_stays_total() {
+        assert_eq!(natural_cmp("apple", "Banana"), Ordering::Less);
+        assert_eq!(natural_cmp("Banana", "cherry"), Ordering::Less);
+        assert_eq!(natural_cmp("Readme", "readme"), Ordering::Less);
+        assert_eq!(natural_cmp("readme", "readme"), Ordering::Equal);
+    }
+
+    #[test]
+    fn sorts_directories_first_even_when_reversed() {
+        let root = fixture("sort", &["v2", "v10", "Docs"]);
+        fs::write(root.join("a.txt"), "").unwrap();
+
+        let names = |order: &SortOrder| {
+            let mut entries: Vec<PathBuf> = fs::read_dir(&root)
+                .unwrap()
+                .map(|entry| entry.unwrap().path())
+                .collect();
+            sort_entries(&mut entries, order);
+            entries
+                .iter()
+                .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+                .collect::<Vec<_>>()
+        };
+
+        let mut order = SortOrder::default();
+        assert_eq!(names(&order), ["Docs", "v2", "v10", "a.txt", "notes.txt"]);
+
+        order.reverse = true;
+        assert_eq!(names(&order), ["v10", "v2", "Docs", "notes.txt", "a.txt"]);
+
+        order.directories_first = false;
+        assert_eq!(names(&order), ["v10", "v2", "notes.txt", "Docs", "a.txt"]);
+
+        fs::remove_dir_all(root).unwrap();
+    }
 }
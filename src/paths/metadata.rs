@@ -0,0 +1,115 @@
+use std::{
+    collections::HashMap,
+    fs,
+    os::unix::fs::{MetadataExt, PermissionsExt},
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use chrono::{DateTime, Local};
+
+/// What the metadata columns show for one listing entry, formatted once when
+/// the directory is read rather than on every draw.
+#[derive(Debug, Clone, Default)]
+pub struct EntryMetadata {
+    /// Empty for directories, their size says nothing useful.
+    pub size: String,
+    pub modified: String,
+    pub permissions: String,
+    pub owner: String,
+    pub symlink_target: Option<PathBuf>,
+}
+
+/// Reads `path` without following a final symlink, so links show their own
+/// permissions next to their target.
+pub fn read_metadata(path: &Path) -> Option<EntryMetadata> {
+    let metadata = fs::symlink_metadata(path).ok()?;
+    let symlink_target = metadata
+        .file_type()
+        .is_symlink()
+        .then(|| fs::read_link(path).ok())
+        .flatten();
+    let is_dir = fs::metadata(path).map(|m| m.is_dir()).unwrap_or(false);
+
+    Some(EntryMetadata {
+        size: if is_dir {
+            String::new()
+        } else {
+            human_size(metadata.len())
+        },
+        modified: metadata
+            .modified()
+            .map(|time| {
+                DateTime::<Local>::from(time)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string()
+            })
+            .unwrap_or_default(),
+        permissions: permission_string(&metadata),
+        owner: user_name(metadata.uid()),
+        symlink_target,
+    })
+}
+
+/// `1023` -> `1023B`, `1536` -> `1.5K`, `10485760` -> `10M`.
+pub fn human_size(bytes: u64) -> String {
+    const UNITS: [&str; 6] = ["B", "K", "M", "G", "T", "P"];
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{}{}", bytes, UNITS[0])
+    } else if size < 10.0 {
+        format!("{:.1}{}", size, UNITS[unit])
+    } else {
+        format!("{:.0}{}", size, UNITS[unit])
+    }
+}
+
+/// `ls -l` style, e.g. `drwxr-xr-x` or `lrwxrwxrwx`.
+fn permission_string(metadata: &fs::Metadata) -> String {
+    let file_type = metadata.file_type();
+    let kind = if file_type.is_symlink() {
+        'l'
+    } else if file_type.is_dir() {
+        'd'
+    } else {
+        '-'
+    };
+
+    let mode = metadata.permissions().mode();
+    let mut permissions = String::from(kind);
+    for shift in [6, 3, 0] {
+        let bits = (mode >> shift) & 0o7;
+        permissions.push(if bits & 0o4 != 0 { 'r' } else { '-' });
+        permissions.push(if bits & 0o2 != 0 { 'w' } else { '-' });
+        permissions.push(if bits & 0o1 != 0 { 'x' } else { '-' });
+    }
+    permissions
+}
+
+/// Looks the uid up in `/etc/passwd`, falling back to the number for users
+/// that only exist in other databases (LDAP, systemd-homed, ...).
+fn user_name(uid: u32) -> String {
+    static USERS: OnceLock<HashMap<u32, String>> = OnceLock::new();
+
+    let users = USERS.get_or_init(|| {
+        fs::read_to_string("/etc/passwd")
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split(':');
+                let name = fields.next()?;
+                let uid = fields.nth(1)?.parse().ok()?;
+                Some((uid, name.to_string()))
+            })
+            .collect()
+    });
+
+    users.get(&uid).cloned().unwrap_or_else(|| uid.to_string())
+}
@@ -1 +1,5 @@
 pub mod directories;
+pub mod metadata;
+pub mod preview;
+pub mod projects;
+pub mod walk;
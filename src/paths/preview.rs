@@ -0,0 +1,181 @@
+use std::{
+    fs::{self, File},
+    io::{self, Read},
+    os::unix::fs::FileTypeExt,
+    path::Path,
+};
+
+/// More lines than any terminal shows, so the pane can grow without
+/// reloading.
+const MAX_LINES: usize = 200;
+const MAX_BYTES: u64 = 64 * 1024;
+const HEX_BYTES: usize = 256;
+
+#[derive(Debug, Clone)]
+pub enum Preview {
+    Text(Vec<String>),
+    Binary {
+        size: u64,
+        /// `xxd` style dump of the first bytes.
+        hex: Vec<String>,
+    },
+    Directory {
+        /// Names, directories end in `/`.
+        entries: Vec<String>,
+        total: usize,
+        /// File name and first lines of a README, so projects are
+        /// recognisable before opening them.
+        readme: Option<(String, Vec<String>)>,
+    },
+    /// FIFOs, sockets and devices, which are not read since that could block.
+    Special(&'static str),
+    Error(String),
+}
+
+pub fn load_preview(path: &Path) -> Preview {
+    let result = if path.is_dir() {
+        preview_directory(path)
+    } else {
+        preview_file(path)
+    };
+    result.unwrap_or_else(|e| Preview::Error(e.to_string()))
+}
+
+fn preview_file(path: &Path) -> io::Result<Preview> {
+    let metadata = fs::metadata(path)?;
+    if !metadata.is_file() {
+        let file_type = metadata.file_type();
+        return Ok(Preview::Special(if file_type.is_fifo() {
+            "fifo"
+        } else if file_type.is_socket() {
+            "socket"
+        } else if file_type.is_char_device() {
+            "character device"
+        } else if file_type.is_block_device() {
+            "block device"
+        } else {
+            "special file"
+        }));
+    }
+
+    let size = metadata.len();
+    let mut bytes = Vec::new();
+    File::open(path)?.take(MAX_BYTES).read_to_end(&mut bytes)?;
+
+    match text_lines(&bytes) {
+        Some(lines) => Ok(Preview::Text(lines)),
+        None => Ok(Preview::Binary {
+            size,
+            hex: hex_dump(&bytes[..bytes.len().min(HEX_BYTES)]),
+        }),
+    }
+}
+
+fn preview_directory(path: &Path) -> io::Result<Preview> {
+    let mut entries: Vec<String> = fs::read_dir(path)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| {
+            let name = printable(&entry.file_name().to_string_lossy());
+            if entry.path().is_dir() {
+                format!("{}/", name)
+            } else {
+                name
+            }
+        })
+        .collect();
+    entries.sort_by_key(|name| (!name.ends_with('/'), name.to_lowercase()));
+    let total = entries.len();
+    entries.truncate(MAX_LINES);
+
+    let readme = entries
+        .iter()
+        .find(|name| {
+            let lower = name.to_lowercase();
+            lower == "readme" || lower.starts_with("readme.")
+        })
+        .and_then(|name| {
+            let Ok(Preview::Text(lines)) = preview_file(&path.join(name)) else {
+                return None;
+            };
+            Some((name.clone(), lines))
+        });
+
+    Ok(Preview::Directory {
+        entries,
+        total,
+        readme,
+    })
+}
+
+/// `None` for binary content: NUL bytes or invalid UTF-8 other than a
+/// character cut off at the read limit.
+fn text_lines(bytes: &[u8]) -> Option<Vec<String>> {
+    if bytes.contains(&0) {
+        return None;
+    }
+
+    let text = match std::str::from_utf8(bytes) {
+        Ok(text) => text,
+        Err(e) if e.error_len().is_none() => std::str::from_utf8(&bytes[..e.valid_up_to()]).ok()?,
+        Err(_) => return None,
+    };
+
+    Some(text.lines().take(MAX_LINES).map(printable).collect())
+}
+
+/// `line` with tabs expanded and ANSI escape sequences and other control
+/// characters removed, so file contents cannot restyle or move the cursor.
+fn printable(line: &str) -> String {
+    let mut result = String::with_capacity(line.len());
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\t' => result.push_str("    "),
+            '\u{1b}' => match chars.next() {
+                // CSI: parameters and intermediates up to a final byte.
+                Some('[') => {
+                    for c in chars.by_ref() {
+                        if ('@'..='~').contains(&c) {
+                            break;
+                        }
+                    }
+                }
+                // OSC: up to BEL or ST (`ESC \`).
+                Some(']') => {
+                    while let Some(c) = chars.next() {
+                        if c == '\u{7}' || (c == '\u{1b}' && chars.next_if_eq(&'\\').is_some()) {
+                            break;
+                        }
+                    }
+                }
+                _ => {}
+            },
+            c if c.is_control() => {}
+            c => result.push(c),
+        }
+    }
+
+    result
+}
+
+fn hex_dump(bytes: &[u8]) -> Vec<String> {
+    bytes
+        .chunks(16)
+        .enumerate()
+        .map(|(row, chunk)| {
+            let hex: Vec<String> = chunk.iter().map(|b| format!("{:02x}", b)).collect();
+            let ascii: String = chunk
+                .iter()
+                .map(|&b| {
+                    if b.is_ascii_graphic() || b == b' ' {
+                        b as char
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+            format!("{:08x}  {:<47}  {}", row * 16, hex.join(" "), ascii)
+        })
+        .collect()
+}
@@ -0,0 +1,80 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    thread,
+};
+
+use crate::{config::settings::ProjectRoot, paths::directories::expand_tilde};
+
+/// Files or directories whose presence marks a project root.
+pub const DEFAULT_PROJECT_MARKERS: [&str; 4] = [".git", "Cargo.toml", "package.json", "go.mod"];
+
+/// Scans every root on its own thread and returns the sorted, deduplicated
+/// list of project directories found below them.
+pub fn discover_projects(roots: &[ProjectRoot], markers: &[String]) -> Vec<PathBuf> {
+    let markers: Vec<&str> = if markers.is_empty() {
+        DEFAULT_PROJECT_MARKERS.to_vec()
+    } else {
+        markers.iter().map(String::as_str).collect()
+    };
+
+    let mut projects: Vec<PathBuf> = thread::scope(|scope| {
+        let handles: Vec<_> = roots
+            .iter()
+            .map(|root| {
+                let markers = &markers;
+                scope.spawn(move || {
+                    let mut found = Vec::new();
+                    scan_for_projects(&expand_tilde(&root.path), root.depth, markers, &mut found);
+                    found
+                })
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap_or_default())
+            .collect()
+    });
+
+    projects.sort();
+    projects.dedup();
+    projects
+}
+
+/// Depth-first walk that stops descending once a directory is a project.
+/// Hidden directories and unreadable ones are skipped.
+fn scan_for_projects(dir: &Path, depth: usize, markers: &[&str], found: &mut Vec<PathBuf>) {
+    if markers.iter().any(|marker| dir.join(marker).exists()) {
+        found.push(dir.to_path_buf());
+        return;
+    }
+
+    if depth == 0 {
+        return;
+    }
+
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in entries.filter_map(|e| e.ok()) {
+        let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+        let hidden = entry.file_name().to_string_lossy().starts_with('.');
+
+        if is_dir && !hidden {
+            scan_for_projects(&entry.path(), depth - 1, markers, found);
+        }
+    }
+}
+
+/// Shortens `$HOME` to `~` for display.
+pub fn display_path(path: &Path) -> String {
+    match std::env::var_os("HOME")
+        .and_then(|home| path.strip_prefix(home).ok().map(Path::to_path_buf))
+    {
+        Some(rest) if rest.as_os_str().is_empty() => "~".to_string(),
+        Some(rest) => format!("~/{}", rest.display()),
+        None => path.display().to_string(),
+    }
+}
@@ -0,0 +1,60 @@
+use std::{
+    path::PathBuf,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver},
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::paths::directories::{ListingFilter, walk_builder};
+
+/// How often the walker hands found directories to the UI.
+const BATCH_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Walks the directories below `root` on a background thread and streams
+/// their paths, relative to `root`, in batches. Entries are skipped as in
+/// directory listings and symlinks are not followed. The walk stops early
+/// once `cancel` is set.
+pub fn spawn_directory_walk(
+    root: PathBuf,
+    filter: ListingFilter,
+    cancel: Arc<AtomicBool>,
+) -> Receiver<Vec<PathBuf>> {
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        let mut batch = Vec::new();
+        let mut last_send = Instant::now();
+
+        let walk = walk_builder(&root, &filter)
+            .filter_entry(|entry| entry.file_type().is_some_and(|t| t.is_dir()))
+            .build();
+
+        for entry in walk.filter_map(|e| e.ok()) {
+            if cancel.load(Ordering::Relaxed) {
+                return;
+            }
+
+            match entry.path().strip_prefix(&root) {
+                Ok(relative) if !relative.as_os_str().is_empty() => {
+                    batch.push(relative.to_path_buf());
+                }
+                _ => {}
+            }
+
+            if last_send.elapsed() >= BATCH_INTERVAL && !batch.is_empty() {
+                if sender.send(std::mem::take(&mut batch)).is_err() {
+                    return;
+                }
+                last_send = Instant::now();
+            }
+        }
+
+        let _ = sender.send(batch);
+    });
+
+    receiver
+}
@@ -0,0 +1,106 @@
+use std::{
+    fs::OpenOptions,
+    io::{self, Write, stdout},
+    panic::{self, PanicHookInfo},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use color_eyre::{Result, config::HookBuilder};
+use ratatui::{
+    Terminal,
+    backend::CrosstermBackend,
+    crossterm::{
+        execute,
+        terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
+    },
+};
+
+/// Draw on `/dev/tty` instead of stdout, set by `--pick` so stdout only
+/// carries the selection.
+static USE_TTY: AtomicBool = AtomicBool::new(false);
+
+pub type Tui = Terminal<CrosstermBackend<Box<dyn Write + Send>>>;
+
+type PanicHook = Box<dyn Fn(&PanicHookInfo<'_>) + Send + Sync + 'static>;
+
+/// Installs color-eyre, with its panic report printed only after the
+/// terminal has been restored. Otherwise a panic leaves the shell in raw
+/// mode on the alternate screen and the report is lost with it.
+pub fn install_hooks() -> Result<()> {
+    let (panic_hook, eyre_hook) = HookBuilder::default().try_into_hooks()?;
+    eyre_hook.install()?;
+    panic::set_hook(restoring_panic_hook(restore, panic_hook.into_panic_hook()));
+    Ok(())
+}
+
+fn restoring_panic_hook(
+    restore: impl Fn() + Send + Sync + 'static,
+    report: PanicHook,
+) -> PanicHook {
+    Box::new(move |info| {
+        restore();
+        report(info);
+    })
+}
+
+/// Where the TUI is drawn.
+pub fn output() -> io::Result<Box<dyn Write + Send>> {
+    if USE_TTY.load(Ordering::Relaxed) {
+        Ok(Box::new(OpenOptions::new().write(true).open("/dev/tty")?))
+    } else {
+        Ok(Box::new(stdout()))
+    }
+}
+
+/// Like `ratatui::init`, without the panic hook it would install on top
+/// of ours, and optionally drawing on `/dev/tty`. Undoes whatever part of
+/// the setup succeeded if it fails.
+pub fn init(use_tty: bool) -> io::Result<Tui> {
+    USE_TTY.store(use_tty, Ordering::Relaxed);
+    let terminal = resume().and_then(|()| Terminal::new(CrosstermBackend::new(output()?)));
+    if terminal.is_err() {
+        restore();
+    }
+    terminal
+}
+
+/// Hands the terminal back to the shell. Errors are ignored, this runs on
+/// the way out, including from the panic hook.
+pub fn restore() {
+    let _ = suspend();
+}
+
+/// Leaves raw mode and the alternate screen, e.g. before attaching tmux.
+pub fn suspend() -> io::Result<()> {
+    disable_raw_mode()?;
+    execute!(output()?, LeaveAlternateScreen)
+}
+
+/// Undoes `suspend`.
+pub fn resume() -> io::Result<()> {
+    enable_raw_mode()?;
+    execute!(output()?, EnterAlternateScreen)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    #[test]
+    fn panic_restores_terminal_before_report() {
+        static EVENTS: Mutex<Vec<&str>> = Mutex::new(Vec::new());
+
+        let previous = panic::take_hook();
+        panic::set_hook(restoring_panic_hook(
+            || EVENTS.lock().unwrap().push("restore"),
+            Box::new(|_| EVENTS.lock().unwrap().push("report")),
+        ));
+        let result = panic::catch_unwind(|| panic!("boom"));
+        panic::set_hook(previous);
+
+        assert!(result.is_err());
+        assert_eq!(*EVENTS.lock().unwrap(), ["restore", "report"]);
+    }
+}
@@ -0,0 +1,80 @@
+use crate::{app::app::App, paths::projects::display_path};
+use ratatui::{
+    Frame,
+    layout::{Alignment, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span, Text},
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+
+/// Popup listing the marks, with the path prompt underneath while one is
+/// being edited.
+pub fn draw(app: &App, frame: &mut Frame) {
+    let mut lines: Vec<Line> = Vec::new();
+
+    if app.state.marks.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "No marks yet, press m<letter> in a directory",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+
+    for (index, (letter, path)) in app.state.marks.iter().enumerate() {
+        let style = if index == app.bookmark_selected_line {
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default()
+        };
+
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!("{}  ", letter),
+                style.patch(Style::default().fg(Color::Cyan)),
+            ),
+            Span::styled(display_path(path), style),
+        ]));
+    }
+
+    lines.push(Line::default());
+    if app.editing_bookmark {
+        lines.push(Line::from(Span::styled(
+            format!("> {}_", app.bookmark_path),
+            Style::default().fg(Color::Cyan),
+        )));
+    } else {
+        lines.push(Line::from(Span::styled(
+            "Enter jump  e edit  d delete  Esc close",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+    if let Some(error) = &app.bookmark_error {
+        lines.push(Line::from(Span::styled(
+            error.as_str(),
+            Style::default().fg(Color::Red),
+        )));
+    }
+
+    let area = frame.area();
+    let popup_width = area.width.saturating_sub(4).min(70);
+    let popup_height = (lines.len() as u16 + 2).min(area.height);
+
+    let popup_area = Rect {
+        x: area.x + (area.width.saturating_sub(popup_width)) / 2,
+        y: area.y + (area.height.saturating_sub(popup_height)) / 2,
+        width: popup_width,
+        height: popup_height,
+    };
+
+    let popup = Paragraph::new(Text::from(lines)).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan))
+            .title(" Marks ")
+            .title_alignment(Alignment::Center),
+    );
+
+    frame.render_widget(Clear, popup_area);
+    frame.render_widget(popup, popup_area);
+}
@@ -1,21 +1,43 @@
-use crate::app::app::App;
+use crate::{
+    app::app::App,
+    paths::metadata::EntryMetadata,
+    ui::search::{self, highlight_positions},
+};
 use ratatui::{
     Frame,
-    layout::{Alignment, Rect},
+    layout::Rect,
     style::{Color, Modifier, Style},
     text::{Line, Span, Text},
     widgets::{Block, Borders, Paragraph, Scrollbar, ScrollbarOrientation},
 };
-use std::time::Duration;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 pub fn draw(app: &mut App, frame: &mut Frame, area: Rect) {
+    if app.subtree_searching {
+        draw_subtree_search(app, frame, area);
+        return;
+    }
+
     let highlight_active = app.searched_string.len() > 1 && !app.search_matches.is_empty();
-    let query = app.searched_string.to_lowercase();
 
     let _ = app
         .search_match_index
         .and_then(|si| app.search_matches.get(si).copied());
 
+    // Inside the borders, minus a column kept free for the scrollbar.
+    let inner_width = area.width.saturating_sub(3) as usize;
+    let columns = if app.state.show_metadata {
+        fitting_columns(app, inner_width)
+    } else {
+        Vec::new()
+    };
+    let name_width = inner_width.saturating_sub(
+        columns
+            .iter()
+            .map(|column| column.width() + 1)
+            .sum::<usize>(),
+    );
+
     let mut lines: Vec<Line> = Vec::new();
 
     for (index, path) in app.current_directory_contents.iter().enumerate() {
@@ -25,43 +47,45 @@ pub fn draw(app: &mut App, frame: &mut Frame, area: Rect) {
             .unwrap_or("<invalid>")
             .to_string();
 
-        let spans: Vec<Span> = if highlight_active && app.search_matches.contains(&index) {
-            let lower_name = file_name.to_lowercase();
-            let mut built = Vec::new();
-            let mut start = 0;
-            let mut remaining = lower_name.as_str();
-
-            let match_style = Style::default()
-                .fg(Color::Cyan)
-                .add_modifier(Modifier::BOLD);
+        let mut spans: Vec<Span> =
+            if highlight_active && let Some(positions) = app.search_highlights.get(&index) {
+                highlight_positions(&file_name, positions)
+            } else if let Some(positions) = app.filter_highlights.get(&index) {
+                highlight_positions(&file_name, positions)
+            } else {
+                vec![Span::raw(file_name.clone())]
+            };
 
-            while let Some(pos) = remaining.find(&query) {
-                let abs_pos = start + pos;
-                let abs_end = abs_pos + query.len();
-
-                if abs_pos > start {
-                    built.push(Span::raw(file_name[start..abs_pos].to_string()));
-                }
+        if let Some(status) = app.git_statuses.get(path) {
+            spans.push(Span::styled(
+                format!(" [{}]", status),
+                Style::default().fg(if status.dirty {
+                    Color::Red
+                } else {
+                    Color::Green
+                }),
+            ));
+        }
 
-                built.push(Span::styled(
-                    file_name[abs_pos..abs_end].to_string(),
-                    match_style,
+        if !columns.is_empty() {
+            let metadata = app.directory_metadata.get(path);
+            if let Some(target) = metadata.and_then(|m| m.symlink_target.as_ref()) {
+                spans.push(Span::styled(
+                    format!(" -> {}", target.to_string_lossy()),
+                    Style::default().fg(Color::DarkGray),
                 ));
-
-                start = abs_end;
-                remaining = &lower_name[start..];
             }
 
-            if start < file_name.len() {
-                built.push(Span::raw(file_name[start..].to_string()));
+            spans = fit_spans(spans, name_width);
+            for column in &columns {
+                let value = metadata.map(|m| column.value(m)).unwrap_or_default();
+                let (value, value_width) = truncate_to_width(value, column.width());
+                spans.push(Span::styled(
+                    format!(" {}{}", " ".repeat(column.width() - value_width), value),
+                    Style::default().fg(Color::DarkGray),
+                ));
             }
-
-            built
-        } else {
-            vec![Span::raw(file_name.clone())]
-        };
-
-        let mut spans = spans;
+        }
 
         if index == app.directories_selected_line {
             for s in spans.iter_mut() {
@@ -77,6 +101,12 @@ pub fn draw(app: &mut App, frame: &mut Frame, area: Rect) {
     }
 
     app.directories_total_lines = lines.len();
+    if let Some(error) = &app.directory_error {
+        lines.push(Line::from(Span::styled(
+            format!("Cannot read this directory: {}", error),
+            Style::default().fg(Color::Red),
+        )));
+    }
     app.directories_visible_height = area.height.saturating_sub(2) as usize;
 
     let max_scroll = app
@@ -101,7 +131,22 @@ pub fn draw(app: &mut App, frame: &mut Frame, area: Rect) {
             Block::default()
                 .borders(Borders::ALL)
                 .border_style(border_style)
-                .title(format!(" {} ", app.current_directory.to_string_lossy())),
+                .title(format!(
+                    " {}{} [{}] ",
+                    app.current_directory.to_string_lossy(),
+                    if app.respect_ignore { " [ignore]" } else { "" },
+                    app.state.sort
+                ))
+                .title_bottom(if app.filtering || !app.directory_filter.is_empty() {
+                    format!(
+                        " filter: {}{} ({}) ",
+                        app.directory_filter,
+                        if app.filtering { "_" } else { "" },
+                        app.current_directory_contents.len()
+                    )
+                } else {
+                    String::new()
+                }),
         )
         .scroll((app.directories_scroll as u16, 0));
 
@@ -115,53 +160,172 @@ pub fn draw(app: &mut App, frame: &mut Frame, area: Rect) {
         &mut app.directories_scroll_state,
     );
 
-    let show_popup = app.searching
-        || app
-            .last_search_update
-            .map(|t| t.elapsed() < Duration::from_secs(2))
-            .unwrap_or(false);
-
-    if show_popup {
-        let total_matches = app.search_matches.len();
-        let current_index = app.search_match_index.map(|i| i + 1).unwrap_or(0);
-
-        let status_text = if app.searching {
-            format!(
-                "/{}  ({} match{})",
-                app.searched_string,
-                total_matches,
-                if total_matches == 1 { "" } else { "es" }
-            )
-        } else if total_matches > 0 {
-            format!(
-                "/{}  [{}/{}]",
-                app.searched_string, current_index, total_matches
-            )
-        } else {
-            format!("/{ }  (no matches)", app.searched_string)
-        };
-
-        // --- Center the popup ---
-        let popup_width = (status_text.len() as u16 + 6).min(area.width.saturating_sub(4));
-        let popup_height = 3;
-
-        let popup_area = Rect {
-            x: area.x + (area.width.saturating_sub(popup_width)) / 2,
-            y: area.y + (area.height.saturating_sub(popup_height)) / 2,
-            width: popup_width,
-            height: popup_height,
-        };
-
-        let popup = Paragraph::new(status_text)
-            .style(Style::default().fg(Color::Cyan))
-            .block(
-                Block::default()
-                    .borders(Borders::ALL)
-                    .border_style(Style::default().fg(Color::Cyan))
-                    .title(" Search ")
-                    .title_alignment(Alignment::Center),
-            );
-
-        frame.render_widget(popup, popup_area);
+    search::draw_popup(app, frame, area);
+}
+
+/// Names narrower than this would be unreadable, columns are dropped first.
+const MIN_NAME_WIDTH: usize = 24;
+
+#[derive(Clone, Copy)]
+enum Column {
+    Size,
+    Modified,
+    Permissions,
+    Owner(usize),
+}
+
+impl Column {
+    fn width(self) -> usize {
+        match self {
+            Column::Size => 6,
+            Column::Modified => 16,
+            Column::Permissions => 10,
+            Column::Owner(width) => width,
+        }
+    }
+
+    fn value(self, metadata: &EntryMetadata) -> &str {
+        match self {
+            Column::Size => &metadata.size,
+            Column::Modified => &metadata.modified,
+            Column::Permissions => &metadata.permissions,
+            Column::Owner(_) => &metadata.owner,
+        }
+    }
+}
+
+/// Columns in display order, dropping the least important ones (owner,
+/// then permissions, then modified time) until the name column fits.
+fn fitting_columns(app: &App, inner_width: usize) -> Vec<Column> {
+    let owner_width = app
+        .directory_metadata
+        .values()
+        .map(|m| m.owner.width())
+        .max()
+        .unwrap_or(0)
+        .min(12);
+
+    let mut columns = vec![
+        Column::Owner(owner_width),
+        Column::Permissions,
+        Column::Modified,
+        Column::Size,
+    ];
+    while !columns.is_empty()
+        && inner_width < MIN_NAME_WIDTH + columns.iter().map(|c| c.width() + 1).sum::<usize>()
+    {
+        columns.remove(0);
+    }
+
+    columns.reverse();
+    columns
+}
+
+/// Cuts `spans` to `width` terminal columns, ending in `…` when cut, and
+/// pads them so the columns after them line up.
+fn fit_spans(spans: Vec<Span<'static>>, width: usize) -> Vec<Span<'static>> {
+    let total: usize = spans.iter().map(|s| s.content.width()).sum();
+    if total <= width {
+        let mut spans = spans;
+        spans.push(Span::raw(" ".repeat(width - total)));
+        return spans;
+    }
+
+    let mut remaining = width.saturating_sub(1);
+    let mut fitted = Vec::new();
+    for span in spans {
+        if remaining == 0 {
+            break;
+        }
+        let (content, content_width) = cut_to_width(&span.content, remaining);
+        let cut = content.len() < span.content.len();
+        remaining -= content_width;
+        fitted.push(Span::styled(content, span.style));
+        if cut {
+            break;
+        }
     }
+    if width > 0 {
+        // Wide chars may leave a column over, keep the padding exact.
+        fitted.push(Span::raw(format!("…{}", " ".repeat(remaining))));
+    }
+    fitted
+}
+
+/// `text` cut to `width` columns with a trailing `…` if it was too wide,
+/// and the width of the result.
+fn truncate_to_width(text: &str, width: usize) -> (String, usize) {
+    if text.width() <= width {
+        return (text.to_string(), text.width());
+    }
+    if width == 0 {
+        return (String::new(), 0);
+    }
+
+    let (mut cut, cut_width) = cut_to_width(text, width - 1);
+    cut.push('…');
+    (cut, cut_width + 1)
+}
+
+/// The longest prefix of `text` that fits in `width` columns, and its width.
+fn cut_to_width(text: &str, width: usize) -> (String, usize) {
+    let mut cut = String::new();
+    let mut cut_width = 0;
+    for c in text.chars() {
+        let char_width = c.width().unwrap_or(0);
+        if cut_width + char_width > width {
+            break;
+        }
+        cut.push(c);
+        cut_width += char_width;
+    }
+    (cut, cut_width)
+}
+
+fn draw_subtree_search(app: &mut App, frame: &mut Frame, area: Rect) {
+    let mut lines: Vec<Line> = Vec::new();
+
+    for (index, (path_index, fuzzy)) in app.subtree_results.iter().enumerate() {
+        let path = &app.subtree_paths[*path_index];
+        let mut spans = highlight_positions(&path.to_string_lossy(), &fuzzy.positions);
+
+        if index == app.subtree_selected_line {
+            for s in spans.iter_mut() {
+                s.style = s.style.patch(
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                );
+            }
+        }
+
+        lines.push(Line::from(spans));
+    }
+
+    app.subtree_visible_height = area.height.saturating_sub(2) as usize;
+
+    let status = if app.subtree_walking {
+        "searching...".to_string()
+    } else if app.subtree_truncated {
+        format!("stopped after {} directories", app.subtree_paths.len())
+    } else {
+        "done".to_string()
+    };
+
+    let paragraph = Paragraph::new(Text::from(lines))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Cyan))
+                .title(format!(
+                    " Search below {} ({} matches, {}) ",
+                    app.current_directory.to_string_lossy(),
+                    app.subtree_results.len(),
+                    status
+                ))
+                .title_bottom(format!(" > {}_ ", app.subtree_query)),
+        )
+        .scroll((app.subtree_scroll as u16, 0));
+
+    frame.render_widget(paragraph, area);
 }
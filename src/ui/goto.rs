@@ -0,0 +1,63 @@
+use crate::app::app::App;
+use ratatui::{
+    Frame,
+    layout::{Alignment, Rect},
+    style::{Color, Style},
+    text::{Line, Span, Text},
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+
+/// Only this many completion candidates are listed under the prompt.
+const MAX_COMPLETIONS: usize = 8;
+
+pub fn draw(app: &App, frame: &mut Frame) {
+    let mut lines = vec![Line::from(Span::styled(
+        format!("> {}_", app.goto_path),
+        Style::default().fg(Color::Cyan),
+    ))];
+
+    for candidate in app.goto_completions.iter().take(MAX_COMPLETIONS) {
+        lines.push(Line::from(Span::styled(
+            format!("  {}/", candidate),
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+    if app.goto_completions.len() > MAX_COMPLETIONS {
+        lines.push(Line::from(Span::styled(
+            format!(
+                "  ... {} more",
+                app.goto_completions.len() - MAX_COMPLETIONS
+            ),
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+    if let Some(error) = &app.goto_error {
+        lines.push(Line::from(Span::styled(
+            error.as_str(),
+            Style::default().fg(Color::Red),
+        )));
+    }
+
+    let area = frame.area();
+    let popup_width = area.width.saturating_sub(4).min(70);
+    let popup_height = (lines.len() as u16 + 2).min(area.height);
+
+    let popup_area = Rect {
+        x: area.x + (area.width.saturating_sub(popup_width)) / 2,
+        y: area.y + (area.height.saturating_sub(popup_height)) / 2,
+        width: popup_width,
+        height: popup_height,
+    };
+
+    let popup = Paragraph::new(Text::from(lines)).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan))
+            .title(" Go to ")
+            .title_bottom(" Tab complete  Enter go  Esc cancel ")
+            .title_alignment(Alignment::Center),
+    );
+
+    frame.render_widget(Clear, popup_area);
+    frame.render_widget(popup, popup_area);
+}
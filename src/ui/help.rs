@@ -23,6 +23,8 @@ pub fn draw(app: &mut App, frame: &mut Frame, popup_area: Rect) {
         Span::styled("Directories", Style::default().fg(Color::Cyan)),
         Span::styled("Tmux", Style::default().fg(Color::Cyan)),
         Span::styled("Search", Style::default().fg(Color::Cyan)),
+        Span::styled("Projects", Style::default().fg(Color::Cyan)),
+        Span::styled("Recent", Style::default().fg(Color::Cyan)),
     ];
 
     let tabs = Tabs::new(tab_titles)
@@ -48,28 +50,81 @@ pub fn draw(app: &mut App, frame: &mut Frame, popup_area: Rect) {
         0 => vec![
             ("H", "Toggle help menu on/off"),
             ("← / →", "Switch between help categories"),
-            ("1 - 4", "Jump directly to a help tab"),
+            ("1 - 6", "Jump directly to a help tab"),
             ("Esc / q", "Exit help"),
             ("↑ / ↓", "Scroll help items"),
+            ("Enter / t", "With --pick, print the selection and exit"),
         ],
         1 => vec![
             ("h / l", "Go back / enter directory"),
+            ("[ / ]", "Back / forward through visited directories"),
+            ("g / :cd", "Go to a path, Tab completes directories"),
+            ("c", "Quit and cd the shell here (needs pinta init)"),
+            ("m<letter>", "Mark the current directory"),
+            ("'<letter>", "Jump to a marked directory"),
+            ("b", "List marks, e edits and d deletes the selected one"),
             ("j / k", "Move down / up"),
             ("A", "Toggle hidden"),
+            ("I", "Toggle .gitignore / exclude filtering"),
+            ("o", "Cycle sort: name, modified, size, extension"),
+            ("O", "Reverse sort order"),
+            ("D", "Toggle directories first"),
+            ("M", "Toggle size, date, permission and owner columns"),
+            ("p", "Toggle preview of the selected entry"),
             ("t", "Open current dir in tmux"),
-            ("/", "Start search"),
-            ("n / N", "Next / previous match"),
+            ("/", "Fuzzy search, the best match is selected first"),
+            ("f", "Pick an entry with external fzf"),
+            ("n / N", "Next / previous match, best first"),
+            ("F", "Filter entries, Enter keeps it, Esc clears it"),
+            ("Enter / t", "Enter / open selected dir while filtered"),
+            ("s", "Search all directories below the current one"),
+            ("w", "List git worktrees, t opens one as repo/branch"),
+            ("w, a", "Create a worktree for a branch and open it"),
+            ("3", "Switch to discovered projects"),
+            ("4", "Switch to recent directories and sessions"),
         ],
         2 => vec![
             ("j / k", "Move between sessions"),
             ("t", "Attach or re-enter selected session"),
+            ("c", "Clone selected session under a new name"),
+            ("f", "Pick a session with external fzf"),
+            ("/", "Search session names"),
+            ("n / N", "Next / previous match, best first"),
+            ("r", "Reload sessions"),
+            (
+                "s",
+                "Start a tmux server (exit-empty off) if none is running",
+            ),
         ],
         3 => vec![
             ("Typing", "Enter search text"),
             ("Backspace", "Delete last character"),
-            ("n / N", "Next / previous match"),
+            ("n / N", "Next / previous match, best first"),
             ("Enter", "Finish search"),
             ("Esc", "Exit search mode"),
+            ("↑ / ↓", "Move in subtree search results"),
+            ("Enter", "Go to subtree search result"),
+            ("Ctrl-t", "Open subtree search result in tmux"),
+        ],
+        4 => vec![
+            ("j / k", "Move down / up"),
+            ("l / Enter", "Browse project in the directories tab"),
+            ("t", "Open project in tmux"),
+            ("/", "Search projects"),
+            ("f", "Pick a project with external fzf"),
+            ("n / N", "Next / previous match, best first"),
+            ("r", "Rescan project roots"),
+            ("p", "Toggle preview with the project README"),
+        ],
+        5 => vec![
+            ("j / k", "Move down / up"),
+            ("l / Enter", "Browse directory in the directories tab"),
+            ("t", "Open directory or attach session"),
+            ("/", "Search recent entries"),
+            ("f", "Pick an entry with external fzf"),
+            ("n / N", "Next / previous match, best first"),
+            ("i", "Import zoxide's database"),
+            ("p", "Toggle preview of the selected directory"),
         ],
         _ => vec![],
     };
@@ -119,6 +174,8 @@ pub fn draw(app: &mut App, frame: &mut Frame, popup_area: Rect) {
                         1 => "Directories",
                         2 => "Tmux",
                         3 => "Search",
+                        4 => "Projects",
+                        5 => "Recent",
                         _ => "",
                     }
                 ))
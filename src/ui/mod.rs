@@ -2,14 +2,21 @@ use ratatui::{
     Frame,
     layout::{Alignment, Constraint, Layout, Rect},
     style::{Color, Style},
-    widgets::{Block, Borders},
+    widgets::{Block, Borders, Paragraph},
 };
 
 use crate::app::app::App;
 
+mod bookmarks;
 mod directories;
+mod goto;
 mod help;
+mod preview;
+mod projects;
+mod recent;
+mod search;
 mod tmux;
+mod worktrees;
 
 pub fn draw(app: &mut App, frame: &mut Frame) {
     let area = frame.area();
@@ -55,6 +62,39 @@ pub fn draw(app: &mut App, frame: &mut Frame) {
         help::draw(app, frame, popup_area);
     }
 
-    directories::draw(app, frame, inner_chunks[0]);
+    // The preview only gets space when the list keeps a usable width.
+    let (list_area, preview_area) = if app.state.show_preview && inner_chunks[0].width >= 60 {
+        let halves = Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(inner_chunks[0]);
+        (halves[0], Some(halves[1]))
+    } else {
+        (inner_chunks[0], None)
+    };
+
+    match app.selected_tab {
+        2 => projects::draw(app, frame, list_area),
+        3 => recent::draw(app, frame, list_area),
+        _ => directories::draw(app, frame, list_area),
+    }
+    if let Some(preview_area) = preview_area {
+        preview::draw(app, frame, preview_area);
+    }
     tmux::draw(app, frame, inner_chunks[1]);
+
+    if app.showing_worktrees {
+        worktrees::draw(app, frame);
+    }
+    if app.showing_bookmarks {
+        bookmarks::draw(app, frame);
+    }
+    if app.going_to {
+        goto::draw(app, frame);
+    }
+
+    if let Some(message) = &app.status_message {
+        frame.render_widget(
+            Paragraph::new(message.as_str()).style(Style::default().fg(Color::Red)),
+            chunks[1],
+        );
+    }
 }
@@ -0,0 +1,98 @@
+use crate::{app::app::App, paths::metadata::human_size, paths::preview::Preview};
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span, Text},
+    widgets::{Block, Borders, Paragraph},
+};
+
+pub fn draw(app: &mut App, frame: &mut Frame, area: Rect) {
+    let dim = Style::default().fg(Color::DarkGray);
+    let heading = Style::default()
+        .fg(Color::Cyan)
+        .add_modifier(Modifier::BOLD);
+
+    let (title, lines) = match app.current_preview() {
+        None => (
+            String::from(" Preview "),
+            vec![Line::from(Span::styled("Nothing selected", dim))],
+        ),
+        Some((path, preview)) => {
+            let name = path
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_else(|| path.to_string_lossy().into_owned());
+
+            let lines = match preview {
+                Preview::Text(text) if text.is_empty() => {
+                    vec![Line::from(Span::styled("Empty file", dim))]
+                }
+                Preview::Text(text) => text.iter().map(|l| Line::raw(l.clone())).collect(),
+                Preview::Binary { size, hex } => {
+                    let mut lines = vec![
+                        Line::from(Span::styled(
+                            format!("Binary file, {}", human_size(*size)),
+                            heading,
+                        )),
+                        Line::default(),
+                    ];
+                    lines.extend(hex.iter().map(|l| Line::styled(l.clone(), dim)));
+                    lines
+                }
+                Preview::Directory {
+                    entries,
+                    total,
+                    readme,
+                } => {
+                    let mut lines = Vec::new();
+                    if let Some((readme_name, readme_lines)) = readme {
+                        lines.push(Line::from(Span::styled(readme_name.clone(), heading)));
+                        lines.extend(readme_lines.iter().take(15).map(|l| Line::raw(l.clone())));
+                        lines.push(Line::default());
+                    }
+
+                    lines.push(Line::from(Span::styled(
+                        format!("{} entries", total),
+                        heading,
+                    )));
+                    lines.extend(entries.iter().map(|entry| {
+                        if entry.ends_with('/') {
+                            Line::styled(entry.clone(), Style::default().fg(Color::Blue))
+                        } else {
+                            Line::raw(entry.clone())
+                        }
+                    }));
+                    if entries.len() < *total {
+                        lines.push(Line::from(Span::styled(
+                            format!("... {} more", total - entries.len()),
+                            dim,
+                        )));
+                    }
+                    lines
+                }
+                Preview::Special(kind) => {
+                    vec![Line::from(Span::styled(
+                        format!("{}, not previewed", kind),
+                        dim,
+                    ))]
+                }
+                Preview::Error(error) => vec![Line::from(Span::styled(
+                    error.clone(),
+                    Style::default().fg(Color::Red),
+                ))],
+            };
+
+            (format!(" {} ", name), lines)
+        }
+    };
+
+    let paragraph = Paragraph::new(Text::from(lines)).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::DarkGray))
+            .title(title),
+    );
+
+    frame.render_widget(paragraph, area);
+}
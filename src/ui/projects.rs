@@ -0,0 +1,102 @@
+use crate::{
+    app::app::App,
+    paths::projects::display_path,
+    ui::search::{self, highlight_positions},
+};
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span, Text},
+    widgets::{Block, Borders, Paragraph, Scrollbar, ScrollbarOrientation},
+};
+
+pub fn draw(app: &mut App, frame: &mut Frame, area: Rect) {
+    let highlight_active = app.searched_string.len() > 1 && !app.search_matches.is_empty();
+
+    let mut lines: Vec<Line> = Vec::new();
+
+    if app.config.project_roots.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "No project roots configured",
+            Style::default().fg(Color::Red),
+        )));
+        lines.push(Line::from(Span::styled(
+            "Add project_roots = [{ path = \"~/code\", depth = 3 }] to ~/.config/pinta/config.toml",
+            Style::default().fg(Color::DarkGray),
+        )));
+    } else if app.projects.is_empty() {
+        lines.push(Line::from(Span::styled(
+            if app.projects_scanning {
+                "Scanning project roots..."
+            } else {
+                "No projects found"
+            },
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+
+    for (index, path) in app.projects.iter().enumerate() {
+        let name = display_path(path);
+
+        let mut spans: Vec<Span> =
+            if highlight_active && let Some(positions) = app.search_highlights.get(&index) {
+                highlight_positions(&name, positions)
+            } else {
+                vec![Span::raw(name)]
+            };
+
+        if index == app.projects_selected_line {
+            for s in spans.iter_mut() {
+                s.style = s.style.patch(
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                );
+            }
+        }
+
+        lines.push(Line::from(spans));
+    }
+
+    app.projects_total_lines = app.projects.len();
+    app.projects_visible_height = area.height.saturating_sub(2) as usize;
+
+    let max_scroll = app
+        .projects_total_lines
+        .saturating_sub(app.projects_visible_height);
+    app.projects_scroll = app.projects_scroll.min(max_scroll);
+
+    app.projects_scroll_state = app
+        .projects_scroll_state
+        .content_length(app.projects_total_lines)
+        .viewport_content_length(app.projects_visible_height)
+        .position(app.projects_scroll);
+
+    let title = if app.projects_scanning {
+        " Projects (scanning...) ".to_string()
+    } else {
+        format!(" Projects ({}) ", app.projects.len())
+    };
+
+    let paragraph = Paragraph::new(Text::from(lines))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Yellow))
+                .title(title),
+        )
+        .scroll((app.projects_scroll as u16, 0));
+
+    frame.render_widget(paragraph, area);
+
+    frame.render_stateful_widget(
+        Scrollbar::new(ScrollbarOrientation::VerticalRight)
+            .begin_symbol(Some("↑"))
+            .end_symbol(Some("↓")),
+        area,
+        &mut app.projects_scroll_state,
+    );
+
+    search::draw_popup(app, frame, area);
+}
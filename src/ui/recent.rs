@@ -0,0 +1,97 @@
+use std::path::Path;
+
+use crate::{
+    app::app::App,
+    config::frecency::{EntryKind, now},
+    paths::projects::display_path,
+    ui::search::{self, highlight_positions},
+};
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span, Text},
+    widgets::{Block, Borders, Paragraph, Scrollbar, ScrollbarOrientation},
+};
+
+pub fn draw(app: &mut App, frame: &mut Frame, area: Rect) {
+    let highlight_active = app.searched_string.len() > 1 && !app.search_matches.is_empty();
+    let now = now();
+
+    let mut lines: Vec<Line> = Vec::new();
+
+    if app.recent.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "Nothing opened yet, press i to import zoxide's database",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+
+    for (index, entry) in app.recent.iter().enumerate() {
+        let (kind, name) = match entry.kind {
+            EntryKind::Directory => ("dir ", display_path(Path::new(&entry.value))),
+            EntryKind::Session => ("tmux", entry.value.clone()),
+        };
+
+        let mut spans = vec![
+            Span::styled(
+                format!("{:>7.1} ", entry.score(now)),
+                Style::default().fg(Color::DarkGray),
+            ),
+            Span::styled(format!("{} ", kind), Style::default().fg(Color::Blue)),
+        ];
+
+        if highlight_active && let Some(positions) = app.search_highlights.get(&index) {
+            spans.extend(highlight_positions(&name, positions));
+        } else {
+            spans.push(Span::raw(name));
+        }
+
+        if index == app.recent_selected_line {
+            for s in spans.iter_mut() {
+                s.style = s.style.patch(
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                );
+            }
+        }
+
+        lines.push(Line::from(spans));
+    }
+
+    app.recent_total_lines = app.recent.len();
+    app.recent_visible_height = area.height.saturating_sub(2) as usize;
+
+    let max_scroll = app
+        .recent_total_lines
+        .saturating_sub(app.recent_visible_height);
+    app.recent_scroll = app.recent_scroll.min(max_scroll);
+
+    app.recent_scroll_state = app
+        .recent_scroll_state
+        .content_length(app.recent_total_lines)
+        .viewport_content_length(app.recent_visible_height)
+        .position(app.recent_scroll);
+
+    let paragraph = Paragraph::new(Text::from(lines))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Yellow))
+                .title(" Recent "),
+        )
+        .scroll((app.recent_scroll as u16, 0));
+
+    frame.render_widget(paragraph, area);
+
+    frame.render_stateful_widget(
+        Scrollbar::new(ScrollbarOrientation::VerticalRight)
+            .begin_symbol(Some("↑"))
+            .end_symbol(Some("↓")),
+        area,
+        &mut app.recent_scroll_state,
+    );
+
+    search::draw_popup(app, frame, area);
+}
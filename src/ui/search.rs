@@ -0,0 +1,102 @@
+use ratatui::{
+    Frame,
+    layout::{Alignment, Rect},
+    style::{Color, Modifier, Style},
+    text::Span,
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+use std::time::Duration;
+
+use crate::app::app::App;
+
+/// Splits `text` into spans with the chars at `positions` highlighted.
+pub fn highlight_positions(text: &str, positions: &[usize]) -> Vec<Span<'static>> {
+    let match_style = Style::default()
+        .fg(Color::Cyan)
+        .add_modifier(Modifier::BOLD);
+
+    let mut built = Vec::new();
+    let mut run = String::new();
+    let mut run_matched = false;
+
+    for (i, c) in text.chars().enumerate() {
+        let matched = positions.contains(&i);
+        if matched != run_matched && !run.is_empty() {
+            let chunk = std::mem::take(&mut run);
+            built.push(if run_matched {
+                Span::styled(chunk, match_style)
+            } else {
+                Span::raw(chunk)
+            });
+        }
+        run_matched = matched;
+        run.push(c);
+    }
+
+    if !run.is_empty() {
+        built.push(if run_matched {
+            Span::styled(run, match_style)
+        } else {
+            Span::raw(run)
+        });
+    }
+
+    built
+}
+
+/// Shows the search query and match count centered in `area` while
+/// searching and for a moment afterwards.
+pub fn draw_popup(app: &App, frame: &mut Frame, area: Rect) {
+    let show_popup = app.searching
+        || app
+            .last_search_update
+            .map(|t| t.elapsed() < Duration::from_secs(2))
+            .unwrap_or(false);
+
+    if !show_popup {
+        return;
+    }
+
+    let total_matches = app.search_matches.len();
+    let current_index = app.search_match_index.map(|i| i + 1).unwrap_or(0);
+
+    let status_text = if app.searching {
+        format!(
+            "/{}  ({} match{})",
+            app.searched_string,
+            total_matches,
+            if total_matches == 1 { "" } else { "es" }
+        )
+    } else if total_matches > 0 {
+        format!(
+            "/{}  [{}/{}]",
+            app.searched_string, current_index, total_matches
+        )
+    } else {
+        format!("/{ }  (no matches)", app.searched_string)
+    };
+
+    // --- Center the popup ---
+    let popup_width = (status_text.len() as u16 + 6).min(area.width.saturating_sub(4));
+    let popup_height = 3;
+
+    let popup_area = Rect {
+        x: area.x + (area.width.saturating_sub(popup_width)) / 2,
+        y: area.y + (area.height.saturating_sub(popup_height)) / 2,
+        width: popup_width,
+        height: popup_height,
+    };
+
+    let popup = Paragraph::new(status_text)
+        .style(Style::default().fg(Color::Cyan))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Cyan))
+                .title(" Search ")
+                .title_alignment(Alignment::Center),
+        );
+
+    frame.render_widget(Clear, popup_area);
+    frame.render_widget(popup, popup_area);
+}
@@ -1,18 +1,67 @@
 use ratatui::{
     Frame,
-    layout::Rect,
+    layout::{Alignment, Constraint, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span, Text},
-    widgets::{Block, Borders, Paragraph, Scrollbar, ScrollbarOrientation},
+    widgets::{Block, Borders, Clear, Paragraph, Scrollbar, ScrollbarOrientation},
 };
 
-use crate::app::app::App;
+use crate::{
+    app::app::App,
+    commands::tmux::{TmuxError, WindowLayout},
+    ui::search::{self, highlight_positions},
+};
+
+const UP: u8 = 1;
+const DOWN: u8 = 2;
+const LEFT: u8 = 4;
+const RIGHT: u8 = 8;
 
 pub fn draw(app: &mut App, frame: &mut Frame, area: Rect) {
+    let show_layouts = area.width >= 60
+        && app
+            .tmux_sessions
+            .get(app.tmux_selected_line)
+            .is_some_and(|s| s.windows.iter().any(|w| w.layout.is_some()));
+
+    let area = if show_layouts {
+        let chunks = Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)])
+            .split(area);
+        draw_layouts(app, frame, chunks[1]);
+        chunks[0]
+    } else {
+        area
+    };
+
+    let highlight_active = app.searched_string.len() > 1 && !app.search_matches.is_empty();
+
     let mut lines: Vec<Line> = Vec::new();
     let mut selected_session_line_index = 0;
 
-    if app.tmux_sessions.is_empty() {
+    if let Some(error) = &app.tmux_error {
+        lines.push(Line::from(Span::styled(
+            error.to_string(),
+            Style::default().fg(Color::Red),
+        )));
+
+        let hint = match error {
+            TmuxError::NotInstalled => "Install tmux and make sure it is in $PATH",
+            TmuxError::NoServer => {
+                "Press s to start a server (with exit-empty off), or t on a directory to create a session"
+            }
+            TmuxError::PermissionDenied(_) => {
+                "Check the owner and mode of the socket in $TMUX_TMPDIR"
+            }
+            TmuxError::VersionTooOld(_) | TmuxError::Unsupported(..) => {
+                "Upgrade tmux to use this feature"
+            }
+            TmuxError::Failed(_) => "Press r to retry",
+        };
+        lines.push(Line::from(Span::styled(
+            hint,
+            Style::default().fg(Color::DarkGray),
+        )));
+    } else if app.tmux_sessions.is_empty() {
         lines.push(Line::from(Span::styled(
             "No tmux sessions found",
             Style::default().fg(Color::Red),
@@ -25,18 +74,24 @@ pub fn draw(app: &mut App, frame: &mut Frame, area: Rect) {
                 selected_session_line_index = current_line_index;
             }
 
-            let session_line = format!("{} (created at {})", session.name, session.date_created);
-            let styled_session_line = if i == app.tmux_selected_line {
-                Line::from(Span::styled(
-                    session_line,
-                    Style::default()
-                        .fg(Color::Yellow)
-                        .add_modifier(Modifier::BOLD),
-                ))
-            } else {
-                Line::from(session_line)
-            };
-            lines.push(styled_session_line);
+            let mut spans =
+                if highlight_active && let Some(positions) = app.search_highlights.get(&i) {
+                    highlight_positions(&session.name, positions)
+                } else {
+                    vec![Span::raw(session.name.clone())]
+                };
+            spans.push(Span::raw(format!(" (created at {})", session.date_created)));
+
+            if i == app.tmux_selected_line {
+                for s in spans.iter_mut() {
+                    s.style = s.style.patch(
+                        Style::default()
+                            .fg(Color::Yellow)
+                            .add_modifier(Modifier::BOLD),
+                    );
+                }
+            }
+            lines.push(Line::from(spans));
             current_line_index += 1;
 
             for window in &session.windows {
@@ -88,7 +143,10 @@ pub fn draw(app: &mut App, frame: &mut Frame, area: Rect) {
             Block::default()
                 .borders(Borders::ALL)
                 .border_style(border_style)
-                .title(" Tmux Sessions "),
+                .title(match app.tmux_version {
+                    Some(version) => format!(" Tmux Sessions (tmux {}) ", version),
+                    None => " Tmux Sessions ".to_string(),
+                }),
         )
         .scroll((app.tmux_scroll as u16, 0))
         .style(Style::default());
@@ -102,4 +160,167 @@ pub fn draw(app: &mut App, frame: &mut Frame, area: Rect) {
         area,
         &mut app.tmux_scroll_state,
     );
+
+    if app.cloning_session {
+        draw_clone_prompt(app, frame);
+    }
+
+    search::draw_popup(app, frame, area);
+}
+
+fn draw_clone_prompt(app: &App, frame: &mut Frame) {
+    let source = app
+        .tmux_sessions
+        .get(app.tmux_selected_line)
+        .map(|s| s.name.as_str())
+        .unwrap_or_default();
+
+    let mut prompt_lines = vec![Line::from(format!("> {}", app.clone_session_name))];
+    if let Some(error) = &app.clone_session_error {
+        prompt_lines.push(Line::from(Span::styled(
+            error.as_str(),
+            Style::default().fg(Color::Red),
+        )));
+    }
+
+    let area = frame.area();
+    let popup_width = area.width.saturating_sub(4).min(50);
+    let popup_height = prompt_lines.len() as u16 + 2;
+
+    let popup_area = Rect {
+        x: area.x + (area.width.saturating_sub(popup_width)) / 2,
+        y: area.y + (area.height.saturating_sub(popup_height)) / 2,
+        width: popup_width,
+        height: popup_height,
+    };
+
+    let popup = Paragraph::new(Text::from(prompt_lines))
+        .style(Style::default().fg(Color::Cyan))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Cyan))
+                .title(format!(" Clone {} as ", source))
+                .title_alignment(Alignment::Center),
+        );
+
+    frame.render_widget(Clear, popup_area);
+    frame.render_widget(popup, popup_area);
+}
+
+/// Draws a mini diagram of each window's pane splits for the selected session.
+fn draw_layouts(app: &App, frame: &mut Frame, area: Rect) {
+    let block = Block::default().borders(Borders::ALL).title(" Layouts ");
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let Some(session) = app.tmux_sessions.get(app.tmux_selected_line) else {
+        return;
+    };
+
+    // One row is kept for the window index under each diagram.
+    let diagram_height = (inner.height as usize).saturating_sub(1).clamp(3, 7);
+    let mut rows: Vec<Vec<Span>> = vec![Vec::new(); diagram_height + 1];
+    let mut used_width = 0;
+
+    for window in &session.windows {
+        let Some(layout) = &window.layout else {
+            continue;
+        };
+
+        // Keep the window's aspect ratio, in terminal cells.
+        let diagram_width =
+            (diagram_height * layout.width as usize / (layout.height.max(1) as usize)).clamp(5, 24);
+        if used_width + diagram_width > inner.width as usize {
+            break;
+        }
+
+        let style = if window.active {
+            Style::default().fg(Color::Yellow)
+        } else {
+            Style::default().fg(Color::DarkGray)
+        };
+
+        let diagram = layout_diagram(layout, diagram_width, diagram_height);
+        for (row, text) in rows.iter_mut().zip(diagram) {
+            row.push(Span::styled(format!("{} ", text), style));
+        }
+        rows[diagram_height].push(Span::styled(
+            format!(
+                "{:^width$} ",
+                format!("[{}]", window.index),
+                width = diagram_width
+            ),
+            style,
+        ));
+
+        used_width += diagram_width + 1;
+    }
+
+    let lines: Vec<Line> = rows.into_iter().map(Line::from).collect();
+    frame.render_widget(Paragraph::new(Text::from(lines)), inner);
+}
+
+/// Renders the pane borders of `layout` scaled into a `width` x `height`
+/// grid of box-drawing characters.
+fn layout_diagram(layout: &WindowLayout, width: usize, height: usize) -> Vec<String> {
+    let mut grid = vec![vec![0u8; width]; height];
+
+    // A pane's borders sit one cell outside it, so the window spans
+    // -1..=size in border coordinates.
+    let scale = |pos: i32, size: u16, cells: usize| -> usize {
+        ((pos + 1) as f32 * (cells - 1) as f32 / (size as f32 + 1.0)).round() as usize
+    };
+
+    for pane in &layout.panes {
+        let x0 = scale(pane.x as i32 - 1, layout.width, width);
+        let x1 = scale((pane.x + pane.width) as i32, layout.width, width);
+        let y0 = scale(pane.y as i32 - 1, layout.height, height);
+        let y1 = scale((pane.y + pane.height) as i32, layout.height, height);
+
+        for y in [y0, y1] {
+            for (x, cell) in grid[y].iter_mut().enumerate().take(x1 + 1).skip(x0) {
+                if x > x0 {
+                    *cell |= LEFT;
+                }
+                if x < x1 {
+                    *cell |= RIGHT;
+                }
+            }
+        }
+
+        for (y, row) in grid.iter_mut().enumerate().take(y1 + 1).skip(y0) {
+            for x in [x0, x1] {
+                if y > y0 {
+                    row[x] |= UP;
+                }
+                if y < y1 {
+                    row[x] |= DOWN;
+                }
+            }
+        }
+    }
+
+    grid.iter()
+        .map(|row| row.iter().map(|&cell| box_char(cell)).collect())
+        .collect()
+}
+
+fn box_char(connections: u8) -> char {
+    match connections {
+        c if c == LEFT | RIGHT => '─',
+        c if c == UP | DOWN => '│',
+        c if c == DOWN | RIGHT => '┌',
+        c if c == DOWN | LEFT => '┐',
+        c if c == UP | RIGHT => '└',
+        c if c == UP | LEFT => '┘',
+        c if c == LEFT | RIGHT | DOWN => '┬',
+        c if c == LEFT | RIGHT | UP => '┴',
+        c if c == UP | DOWN | RIGHT => '├',
+        c if c == UP | DOWN | LEFT => '┤',
+        c if c == UP | DOWN | LEFT | RIGHT => '┼',
+        c if c & (LEFT | RIGHT) != 0 => '─',
+        c if c & (UP | DOWN) != 0 => '│',
+        _ => ' ',
+    }
 }
@@ -0,0 +1,84 @@
+use crate::{app::app::App, commands::git::repository_name, paths::projects::display_path};
+use ratatui::{
+    Frame,
+    layout::{Alignment, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span, Text},
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+
+/// Popup listing the worktrees of a repository, with the new worktree
+/// prompt underneath while it is open.
+pub fn draw(app: &App, frame: &mut Frame) {
+    let label_width = app
+        .checked_out_worktrees()
+        .map(|w| w.label().chars().count())
+        .max()
+        .unwrap_or(0);
+
+    let mut lines: Vec<Line> = Vec::new();
+    for (index, worktree) in app.checked_out_worktrees().enumerate() {
+        let style = if index == app.worktree_selected_line {
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default()
+        };
+
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!("{:<width$}  ", worktree.label(), width = label_width),
+                style,
+            ),
+            Span::styled(
+                display_path(&worktree.path),
+                style.patch(Style::default().fg(Color::DarkGray)),
+            ),
+        ]));
+    }
+
+    lines.push(Line::default());
+    if app.creating_worktree {
+        lines.push(Line::from(Span::styled(
+            format!("New worktree for branch > {}_", app.worktree_branch),
+            Style::default().fg(Color::Cyan),
+        )));
+    } else {
+        lines.push(Line::from(Span::styled(
+            "t open  l browse  a new worktree  Esc close",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+    if let Some(error) = &app.worktree_error {
+        lines.push(Line::from(Span::styled(
+            error.as_str(),
+            Style::default().fg(Color::Red),
+        )));
+    }
+
+    let area = frame.area();
+    let popup_width = area.width.saturating_sub(4).min(70);
+    let popup_height = (lines.len() as u16 + 2).min(area.height);
+
+    let popup_area = Rect {
+        x: area.x + (area.width.saturating_sub(popup_width)) / 2,
+        y: area.y + (area.height.saturating_sub(popup_height)) / 2,
+        width: popup_width,
+        height: popup_height,
+    };
+
+    let popup = Paragraph::new(Text::from(lines)).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan))
+            .title(format!(
+                " Worktrees of {} ",
+                repository_name(&app.worktrees)
+            ))
+            .title_alignment(Alignment::Center),
+    );
+
+    frame.render_widget(Clear, popup_area);
+    frame.render_widget(popup, popup_area);
+}